@@ -1,3 +1,29 @@
+// The catalog module is shared with the app so the build checks exactly what the app will parse
+#[allow(dead_code)]
+#[path = "src/commands/version.rs"]
+mod version;
+
+#[allow(dead_code)]
+#[path = "src/commands/catalog.rs"]
+mod catalog;
+
 fn main() {
+    println!("cargo:rerun-if-changed=data/catalog.json");
+    println!("cargo:rerun-if-changed=src/commands/catalog.rs");
+    println!("cargo:rerun-if-changed=src/commands/version.rs");
+    println!("cargo:rerun-if-changed=data/catalog_keys.txt");
+
+    let json = std::fs::read_to_string("data/catalog.json")
+        .unwrap_or_else(|e| panic!("Could not read data/catalog.json: {}", e));
+    if let Err(e) = catalog::Catalog::parse(&json) {
+        panic!("data/catalog.json is malformed: {}", e);
+    }
+
+    let keys = std::fs::read_to_string("data/catalog_keys.txt")
+        .unwrap_or_else(|e| panic!("Could not read data/catalog_keys.txt: {}", e));
+    if let Err(e) = catalog::parse_signing_keys(&keys) {
+        panic!("data/catalog_keys.txt is malformed: {}", e);
+    }
+
     tauri_build::build()
 }
@@ -0,0 +1,155 @@
+//! Installer pinning tool
+//! `cargo run --example pin_installers -- data/catalog.json` downloads every installer the catalog
+//! has no hash for and writes its `size` and `sha256` into the entry;
+//! `cargo run --example pin_installers -- data/catalog.json --all` re-downloads pinned installers
+//! too and reports any that no longer match.
+//!
+//! Run it from a trusted network, review the diff, bump `catalog_version` and re-sign the catalog
+//! with sign_catalog before publishing.
+
+// The catalog module is shared with the app so the tool writes exactly what the app will parse
+#[allow(dead_code)]
+#[path = "../src/commands/version.rs"]
+mod version;
+
+#[allow(dead_code)]
+#[path = "../src/commands/catalog.rs"]
+mod catalog;
+
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use catalog::Catalog;
+
+/// Size and hex SHA-256 of an installer
+type Pin = (u64, String);
+
+fn hash_url(agent: &ureq::Agent, url: &str) -> Result<Pin, String> {
+    // ureq errors already name the URL
+    let response = agent.get(url).call().map_err(|e| e.to_string())?;
+    let mut reader = response.into_reader();
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut size = 0u64;
+    loop {
+        let read = reader
+            .read(&mut buffer)
+            .map_err(|e| format!("{}: {}", url, e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+    let sha256 = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Ok((size, sha256))
+}
+
+/// Pin one entry, returning a description of what changed
+fn pin(
+    agent: &ureq::Agent,
+    pins: &mut HashMap<String, Pin>,
+    url: &str,
+    size: &mut Option<u64>,
+    sha256: &mut Option<String>,
+) -> Result<Option<String>, String> {
+    let (new_size, new_hash) = match pins.get(url) {
+        Some(pin) => pin.clone(),
+        None => {
+            eprintln!("Downloading {}", url);
+            let pin = hash_url(agent, url)?;
+            pins.insert(url.to_string(), pin.clone());
+            pin
+        }
+    };
+    let change = match sha256.as_deref() {
+        None => Some(format!("pinned {} ({} bytes)", new_hash, new_size)),
+        Some(old) if old != new_hash || *size != Some(new_size) => Some(format!(
+            "CHANGED from {} to {} ({} bytes) - check the installer before publishing",
+            old, new_hash, new_size
+        )),
+        Some(_) => None,
+    };
+    *size = Some(new_size);
+    *sha256 = Some(new_hash);
+    Ok(change)
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let (path, all) = match args {
+        [path] => (path, false),
+        [path, flag] if flag == "--all" => (path, true),
+        _ => return Err("Usage: pin_installers <catalog.json> [--all]".into()),
+    };
+    let json = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+    let mut catalog = Catalog::parse(&json)?;
+
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(20))
+        .timeout_read(Duration::from_secs(60))
+        .build();
+    let mut pins: HashMap<String, Pin> = HashMap::new();
+    let mut failures = Vec::new();
+    let mut changed = 0;
+
+    let entries = catalog
+        .versions
+        .iter_mut()
+        .map(|v| (&v.label, &v.url, &mut v.size, &mut v.sha256))
+        .chain(
+            catalog
+                .personas
+                .iter_mut()
+                .map(|p| (&p.name, &p.url, &mut p.size, &mut p.sha256)),
+        );
+    for (name, url, size, sha256) in entries {
+        if sha256.is_some() && !all {
+            continue;
+        }
+        match pin(&agent, &mut pins, url, size, sha256) {
+            Ok(Some(change)) => {
+                changed += 1;
+                println!("{}: {}", name, change);
+            }
+            Ok(None) => {}
+            Err(e) => failures.push(format!("{}: {}", name, e)),
+        }
+    }
+
+    catalog.validate().map_err(|errors| errors.join("\n"))?;
+    if changed > 0 {
+        let content = serde_json::to_string_pretty(&catalog).map_err(|e| e.to_string())? + "\n";
+        fs::write(path, content).map_err(|e| format!("Could not write {}: {}", path, e))?;
+        println!("{} entries updated in {}", changed, path);
+    } else {
+        println!("Nothing to update");
+    }
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} entries could not be downloaded:\n{}",
+            failures.len(),
+            failures.join("\n")
+        ))
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Catalog signing tool
+//! `cargo run --example sign_catalog -- keygen <key-file>` creates a signing key and prints its public key;
+//! `cargo run --example sign_catalog -- sign <key-file> <catalog.json>` writes catalog.json.sig
+//!
+//! The release signing key is held by the maintainer who publishes releases and never enters the
+//! repository. Its public half is listed in data/catalog_keys.txt; the app accepts a catalog signed
+//! by any key listed there. To rotate: keygen a new key, add its public key to catalog_keys.txt
+//! beside the old one and release; once that release is out, sign with the new key only and
+//! remove the old line in the following release. See docs/Features/legacy-downloads.md.
+
+use ed25519_dalek::{Signer, SigningKey};
+use std::fs;
+use std::process::ExitCode;
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn read_key(path: &str) -> Result<SigningKey, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+    let text = text.trim();
+    let bytes: Vec<u8> = (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2).unwrap_or("zz"), 16))
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("{} is not a hex-encoded key", path))?;
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| format!("{} must hold 32 bytes", path))?;
+    Ok(SigningKey::from_bytes(&bytes))
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    match args {
+        [command, key_path] if command == "keygen" => {
+            let key = SigningKey::generate(&mut rand_core::OsRng);
+            fs::write(key_path, to_hex(&key.to_bytes())).map_err(|e| e.to_string())?;
+            println!(
+                "Signing key written to {} - keep it out of the repository",
+                key_path
+            );
+            println!(
+                "Public key (add this line to data/catalog_keys.txt): {}",
+                to_hex(&key.verifying_key().to_bytes())
+            );
+            Ok(())
+        }
+        [command, key_path, catalog_path] if command == "sign" => {
+            let key = read_key(key_path)?;
+            let content = fs::read(catalog_path)
+                .map_err(|e| format!("Could not read {}: {}", catalog_path, e))?;
+            let signature = key.sign(&content);
+            let signature_path = format!("{}.sig", catalog_path);
+            fs::write(&signature_path, to_hex(&signature.to_bytes())).map_err(|e| e.to_string())?;
+            println!("Signature written to {}", signature_path);
+            Ok(())
+        }
+        _ => Err("Usage: sign_catalog keygen <key-file> | sign <key-file> <catalog.json>".into()),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -39,9 +39,7 @@ pub struct RestoreResult {
 
 /// Get the backup directory path
 fn get_backup_dir() -> Option<PathBuf> {
-    std::env::var("LOCALAPPDATA")
-        .ok()
-        .map(|p| PathBuf::from(p).join("CCVersionGuard").join("Backups"))
+    super::paths::get_guard_data_dir().map(|p| p.join("Backups"))
 }
 
 /// Calculate directory size
@@ -129,7 +127,7 @@ pub fn create_backup(version_path: &Path, reason: &str) -> BackupResult {
 }
 
 /// Copy directory recursively
-fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<(), String> {
+pub(crate) fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<(), String> {
     if !src.is_dir() {
         return Err("Source is not a directory".to_string());
     }
@@ -0,0 +1,696 @@
+//! Version catalog
+//! Typed model of data/catalog.json - every downloadable version plus the curated personas
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use super::version::{CapCutVersion, ChannelKind, InstallerName};
+
+/// Catalog format this build understands
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Risk levels from lowest to highest
+pub const RISK_LEVELS: &[&str] = &["Low", "Medium", "High"];
+
+/// Catalog shipped with the app (validated by build.rs)
+const EMBEDDED_CATALOG: &str =
+    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/data/catalog.json"));
+
+/// The complete version catalog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Catalog {
+    pub schema_version: u32,
+    /// Increases with every published catalog
+    pub catalog_version: u64,
+    /// Label of the newest version CC Version Guard works with
+    pub last_compatible: String,
+    pub personas: Vec<Persona>,
+    /// Feature knowledge base
+    #[serde(default)]
+    pub features: Vec<Feature>,
+    /// Risk factors, summed per version
+    #[serde(default)]
+    pub risk_rules: Vec<RiskRule>,
+    /// Points at which a version reaches each risk level above "Low"
+    #[serde(default)]
+    pub risk_thresholds: Vec<RiskThreshold>,
+    /// Every known release, newest first
+    pub versions: Vec<CatalogVersion>,
+}
+
+/// A curated version recommended for a kind of user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Persona {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub features: Vec<String>,
+    pub url: String,
+    /// Alternative URLs for the same installer, tried in order after `url`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<String>,
+    /// Curated rating; replaces the rule-based level when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk_level: Option<String>,
+    /// Installer size in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Lowercase hex SHA-256 of the installer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+/// One downloadable release
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CatalogVersion {
+    /// "5.4.0 (Beta6)"
+    pub label: String,
+    pub url: String,
+    /// Alternative URLs for the same installer, tried in order after `url`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Installer size in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Lowercase hex SHA-256 of the installer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+/// Size and hash a downloaded installer must match
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExpectedInstaller {
+    pub size: Option<u64>,
+    pub sha256: Option<String>,
+}
+
+/// Where to download an installer from and what it must match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstallerSource {
+    /// Primary URL first, then mirrors in priority order
+    pub urls: Vec<String>,
+    pub expected: ExpectedInstaller,
+}
+
+/// How a feature can be used in a release
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Access {
+    Free,
+    /// Present but behind the Pro paywall
+    Paid,
+    Unavailable,
+}
+
+/// Releases `from` through `until` (inclusive, by major.minor.patch); open-ended when omitted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeatureRange {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    pub access: Access,
+}
+
+/// A named feature and where it is available
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Feature {
+    /// Stable identifier ("auto_captions")
+    pub id: String,
+    pub name: String,
+    pub category: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Non-overlapping ranges; releases outside all of them are unknown
+    pub availability: Vec<FeatureRange>,
+}
+
+/// major.minor.patch of a range bound
+fn release(bound: Option<&str>) -> Option<(u32, u32, u32)> {
+    bound
+        .and_then(CapCutVersion::parse)
+        .map(|v| (v.major, v.minor, v.patch))
+}
+
+/// Whether a version's release lies within optional inclusive bounds
+fn in_range(from: Option<&str>, until: Option<&str>, version: &CapCutVersion) -> bool {
+    let v = (version.major, version.minor, version.patch);
+    release(from).is_none_or(|from| from <= v) && release(until).is_none_or(|until| v <= until)
+}
+
+impl FeatureRange {
+    fn contains(&self, version: &CapCutVersion) -> bool {
+        in_range(self.from.as_deref(), self.until.as_deref(), version)
+    }
+}
+
+impl Feature {
+    /// Access in a release, or None if the knowledge base does not say
+    pub fn access(&self, version: &CapCutVersion) -> Option<Access> {
+        self.availability
+            .iter()
+            .find(|r| r.contains(version))
+            .map(|r| r.access)
+    }
+
+    fn validate(&self, errors: &mut Vec<String>) {
+        if self.availability.is_empty() {
+            errors.push(format!("{}: feature has no availability", self.id));
+        }
+        for range in &self.availability {
+            for bound in [&range.from, &range.until].into_iter().flatten() {
+                if CapCutVersion::parse(bound).is_none() {
+                    errors.push(format!("{}: {} is not a version", self.id, bound));
+                }
+            }
+            if let (Some(from), Some(until)) = (
+                release(range.from.as_deref()),
+                release(range.until.as_deref()),
+            ) {
+                if from > until {
+                    errors.push(format!("{}: range ends before it starts", self.id));
+                }
+            }
+        }
+        // Ranges overlap if one starts before another ends
+        for (i, a) in self.availability.iter().enumerate() {
+            for b in &self.availability[i + 1..] {
+                let a_before_b = release(a.until.as_deref())
+                    .zip(release(b.from.as_deref()))
+                    .is_some_and(|(end, start)| end < start);
+                let b_before_a = release(b.until.as_deref())
+                    .zip(release(a.from.as_deref()))
+                    .is_some_and(|(end, start)| end < start);
+                if !a_before_b && !b_before_a {
+                    errors.push(format!("{}: availability ranges overlap", self.id));
+                }
+            }
+        }
+    }
+}
+
+/// What a risk rule matches; every field that is set must match
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuleCondition {
+    /// First release the rule applies to (by major.minor.patch)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// Last release the rule applies to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    /// Any of these channels
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub channel: Vec<ChannelKind>,
+    /// Any of these feature ids is behind the paywall
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub paywalled: Vec<String>,
+    /// Whether CC Version Guard supports the version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compatible: Option<bool>,
+}
+
+/// One risk factor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RiskRule {
+    pub id: String,
+    pub when: RuleCondition,
+    pub points: u32,
+    /// Shown to the user when the rule applies
+    pub reason: String,
+}
+
+/// Minimum points for a risk level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RiskThreshold {
+    pub level: String,
+    pub points: u32,
+}
+
+impl RuleCondition {
+    /// Whether the condition holds for a version
+    pub fn matches(&self, version: &CapCutVersion, compatible: bool, features: &[Feature]) -> bool {
+        in_range(self.from.as_deref(), self.until.as_deref(), version)
+            && (self.channel.is_empty() || self.channel.contains(&version.channel.kind()))
+            && (self.paywalled.is_empty()
+                || features.iter().any(|f| {
+                    self.paywalled.contains(&f.id) && f.access(version) == Some(Access::Paid)
+                }))
+            && self.compatible.is_none_or(|c| c == compatible)
+    }
+}
+
+/// User-editable changes layered on top of the catalog (overrides.json)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CatalogOverrides {
+    #[serde(default)]
+    pub last_compatible: Option<String>,
+    /// Added, or replacing the entry with the same label
+    #[serde(default)]
+    pub versions: Vec<CatalogVersion>,
+    /// Added, or replacing the persona with the same name
+    #[serde(default)]
+    pub personas: Vec<Persona>,
+    /// Added, or replacing the feature with the same id
+    #[serde(default)]
+    pub features: Vec<Feature>,
+    /// Added, or replacing the risk rule with the same id
+    #[serde(default)]
+    pub risk_rules: Vec<RiskRule>,
+    /// Version labels, persona names, feature or risk rule ids to drop
+    #[serde(default)]
+    pub remove: Vec<String>,
+}
+
+/// Decode a hex string (surrounding whitespace ignored)
+pub fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim();
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Parse the catalog signing keys file: one hex-encoded ed25519 public key per line, `#` comments
+pub fn parse_signing_keys(text: &str) -> Result<Vec<[u8; 32]>, String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            decode_hex(line)
+                .and_then(|key| key.try_into().ok())
+                .ok_or_else(|| format!("{} is not a hex-encoded 32-byte public key", line))
+        })
+        .collect()
+}
+
+/// Position of a risk level from lowest to highest
+pub fn risk_rank(level: &str) -> Option<usize> {
+    RISK_LEVELS
+        .iter()
+        .position(|r| r.eq_ignore_ascii_case(level))
+}
+
+impl CatalogVersion {
+    /// Installer identity from the download URL
+    pub fn installer(&self) -> Option<InstallerName> {
+        InstallerName::parse(&self.url)
+    }
+
+    /// Version from the label with the build number from the URL
+    pub fn identity(&self) -> Option<CapCutVersion> {
+        let label = CapCutVersion::parse(&self.label)?;
+        Some(CapCutVersion {
+            build: self.installer().and_then(|i| i.version.build),
+            ..label
+        })
+    }
+}
+
+fn check_integrity(size: Option<u64>, sha256: Option<&str>, what: &str, errors: &mut Vec<String>) {
+    if size == Some(0) {
+        errors.push(format!("{}: size must be positive", what));
+    }
+    if let Some(hash) = sha256 {
+        let is_hex = hash
+            .chars()
+            .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c));
+        if hash.len() != 64 || !is_hex {
+            errors.push(format!(
+                "{}: sha256 must be 64 lowercase hex characters",
+                what
+            ));
+        }
+    }
+}
+
+/// Mirrors must be distinct installer links, and any versioned name must be the same build
+fn check_mirrors(url: &str, mirrors: &[String], what: &str, errors: &mut Vec<String>) {
+    let primary = InstallerName::parse(url);
+    let mut seen = HashSet::from([url]);
+    for mirror in mirrors {
+        if !seen.insert(mirror.as_str()) {
+            errors.push(format!("{}: duplicate mirror {}", what, mirror));
+        }
+        check_url(mirror, what, errors);
+        if let (Some(primary), Some(installer)) = (&primary, InstallerName::parse(mirror)) {
+            let same_build = installer.version.same_release(&primary.version)
+                && installer.version.build == primary.version.build;
+            if !same_build {
+                errors.push(format!(
+                    "{}: mirror {} is for {}",
+                    what, mirror, installer.version
+                ));
+            }
+        }
+    }
+}
+
+fn check_url(url: &str, what: &str, errors: &mut Vec<String>) {
+    if !url.starts_with("https://") {
+        errors.push(format!("{}: URL must use https ({})", what, url));
+    } else if !url.to_ascii_lowercase().ends_with(".exe") {
+        errors.push(format!(
+            "{}: URL does not point to an installer ({})",
+            what, url
+        ));
+    }
+}
+
+impl Catalog {
+    /// Parse and validate catalog JSON
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let catalog: Catalog =
+            serde_json::from_str(json).map_err(|e| format!("Invalid catalog: {}", e))?;
+        catalog.validate().map_err(|errors| errors.join("; "))?;
+        Ok(catalog)
+    }
+
+    /// Check everything serde cannot: versions parse, labels and URLs are unique, links are https
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if self.schema_version != SCHEMA_VERSION {
+            errors.push(format!(
+                "Unsupported schema version {} (expected {})",
+                self.schema_version, SCHEMA_VERSION
+            ));
+        }
+        if self.versions.is_empty() {
+            errors.push("Catalog lists no versions".to_string());
+        }
+
+        let mut labels = HashSet::new();
+        let mut urls = HashSet::new();
+        let mut builds = HashSet::new();
+        for entry in &self.versions {
+            if CapCutVersion::parse(&entry.label).is_none() {
+                errors.push(format!("{}: label is not a version", entry.label));
+            }
+            if !labels.insert(entry.label.as_str()) {
+                errors.push(format!("{}: duplicate label", entry.label));
+            }
+            for url in std::iter::once(&entry.url).chain(&entry.mirrors) {
+                if !urls.insert(url.as_str()) {
+                    errors.push(format!("{}: duplicate URL {}", entry.label, url));
+                }
+            }
+            check_url(&entry.url, &entry.label, &mut errors);
+            check_mirrors(&entry.url, &entry.mirrors, &entry.label, &mut errors);
+            check_integrity(
+                entry.size,
+                entry.sha256.as_deref(),
+                &entry.label,
+                &mut errors,
+            );
+            let Some(installer) = entry.installer() else {
+                errors.push(format!(
+                    "{}: URL does not name a versioned installer",
+                    entry.label
+                ));
+                continue;
+            };
+            if let Some(label) = CapCutVersion::parse(&entry.label) {
+                if !installer.version.same_release(&label) {
+                    errors.push(format!(
+                        "{}: URL is for {}",
+                        entry.label,
+                        installer.version.base()
+                    ));
+                } else if !builds.insert((label.base(), installer.version.build)) {
+                    errors.push(format!("{}: duplicate build", entry.label));
+                }
+            }
+        }
+        if !labels.contains(self.last_compatible.as_str()) {
+            errors.push(format!(
+                "last_compatible {} is not in the version list",
+                self.last_compatible
+            ));
+        }
+
+        let mut names = HashSet::new();
+        for persona in &self.personas {
+            if persona.name.trim().is_empty() {
+                errors.push("Persona without a name".to_string());
+            }
+            if !names.insert(persona.name.as_str()) {
+                errors.push(format!("{}: duplicate persona", persona.name));
+            }
+            if CapCutVersion::parse(&persona.version).is_none() {
+                errors.push(format!("{}: version is not a version", persona.name));
+            }
+            if let Some(level) = persona.risk_level.as_deref() {
+                if risk_rank(level).is_none() {
+                    errors.push(format!("{}: unknown risk level {}", persona.name, level));
+                }
+            }
+            check_url(&persona.url, &persona.name, &mut errors);
+            check_mirrors(&persona.url, &persona.mirrors, &persona.name, &mut errors);
+            check_integrity(
+                persona.size,
+                persona.sha256.as_deref(),
+                &persona.name,
+                &mut errors,
+            );
+        }
+
+        let mut ids = HashSet::new();
+        for feature in &self.features {
+            if !ids.insert(feature.id.as_str()) {
+                errors.push(format!("{}: duplicate feature", feature.id));
+            }
+            feature.validate(&mut errors);
+        }
+
+        let mut rule_ids = HashSet::new();
+        for rule in &self.risk_rules {
+            if !rule_ids.insert(rule.id.as_str()) {
+                errors.push(format!("{}: duplicate risk rule", rule.id));
+            }
+            for bound in [&rule.when.from, &rule.when.until].into_iter().flatten() {
+                if CapCutVersion::parse(bound).is_none() {
+                    errors.push(format!("{}: {} is not a version", rule.id, bound));
+                }
+            }
+            for id in &rule.when.paywalled {
+                if !ids.contains(id.as_str()) {
+                    errors.push(format!("{}: unknown feature {}", rule.id, id));
+                }
+            }
+        }
+
+        let mut previous: Option<(usize, u32)> = None;
+        for threshold in &self.risk_thresholds {
+            let Some(rank) = risk_rank(&threshold.level) else {
+                errors.push(format!("Unknown risk level {}", threshold.level));
+                continue;
+            };
+            if previous.is_some_and(|(r, p)| rank <= r || threshold.points <= p) {
+                errors.push(format!(
+                    "Risk threshold {} must be higher than the one before it",
+                    threshold.level
+                ));
+            }
+            previous = Some((rank, threshold.points));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Apply overrides and re-validate the result
+    pub fn merge(&self, overrides: &CatalogOverrides) -> Result<Catalog, String> {
+        let mut merged = self.clone();
+        merged
+            .versions
+            .retain(|v| !overrides.remove.contains(&v.label));
+        merged
+            .personas
+            .retain(|p| !overrides.remove.contains(&p.name));
+        merged
+            .features
+            .retain(|f| !overrides.remove.contains(&f.id));
+        merged
+            .risk_rules
+            .retain(|r| !overrides.remove.contains(&r.id));
+
+        for entry in &overrides.versions {
+            if let Some(existing) = merged.versions.iter_mut().find(|v| v.label == entry.label) {
+                *existing = entry.clone();
+                continue;
+            }
+            // Keep the list newest first
+            let parsed = CapCutVersion::parse(&entry.label);
+            let position = merged
+                .versions
+                .iter()
+                .position(|v| CapCutVersion::parse(&v.label) < parsed)
+                .unwrap_or(merged.versions.len());
+            merged.versions.insert(position, entry.clone());
+        }
+        for persona in &overrides.personas {
+            match merged.personas.iter_mut().find(|p| p.name == persona.name) {
+                Some(existing) => *existing = persona.clone(),
+                None => merged.personas.push(persona.clone()),
+            }
+        }
+        for feature in &overrides.features {
+            match merged.features.iter_mut().find(|f| f.id == feature.id) {
+                Some(existing) => *existing = feature.clone(),
+                None => merged.features.push(feature.clone()),
+            }
+        }
+        for rule in &overrides.risk_rules {
+            match merged.risk_rules.iter_mut().find(|r| r.id == rule.id) {
+                Some(existing) => *existing = rule.clone(),
+                None => merged.risk_rules.push(rule.clone()),
+            }
+        }
+        if let Some(label) = &overrides.last_compatible {
+            merged.last_compatible = label.clone();
+        }
+
+        merged.validate().map_err(|errors| errors.join("; "))?;
+        Ok(merged)
+    }
+
+    /// Entry for the same release and build number
+    pub fn find_build(&self, version: &CapCutVersion) -> Option<&CatalogVersion> {
+        version.build?;
+        self.versions.iter().find(|entry| {
+            entry
+                .identity()
+                .is_some_and(|id| id.same_release(version) && id.build == version.build)
+        })
+    }
+
+    /// Download URLs and known size and hash of the installer at a catalog URL (primary or
+    /// mirror); `None` if no entry links to it
+    pub fn installer_source(&self, url: &str) -> Option<InstallerSource> {
+        let links = |primary: &String, mirrors: &[String]| {
+            primary == url || mirrors.iter().any(|m| m == url)
+        };
+        let version = self
+            .versions
+            .iter()
+            .find(|v| links(&v.url, &v.mirrors))
+            .map(|v| (&v.url, &v.mirrors, v.size, &v.sha256));
+        let persona = self
+            .personas
+            .iter()
+            .find(|p| links(&p.url, &p.mirrors))
+            .map(|p| (&p.url, &p.mirrors, p.size, &p.sha256));
+        version
+            .or(persona)
+            .map(|(primary, mirrors, size, sha256)| InstallerSource {
+                urls: std::iter::once(primary).chain(mirrors).cloned().collect(),
+                expected: ExpectedInstaller {
+                    size,
+                    sha256: sha256.clone(),
+                },
+            })
+    }
+
+    /// Whether CC Version Guard supports a version. Builds are ignored: a listed build is
+    /// compared with the channel its catalog label names, since folder names and PE versions
+    /// ("5.4.0.1991") carry none.
+    pub fn is_compatible(&self, version: &CapCutVersion) -> bool {
+        let Some(last) = self.last_compatible_version() else {
+            return true;
+        };
+        let version = self
+            .find_build(version)
+            .and_then(|entry| entry.identity())
+            .unwrap_or(*version);
+        let release = |v: &CapCutVersion| (v.major, v.minor, v.patch, v.channel);
+        release(&version) <= release(&last)
+    }
+
+    /// Parsed form of `last_compatible`
+    pub fn last_compatible_version(&self) -> Option<CapCutVersion> {
+        CapCutVersion::parse(&self.last_compatible)
+    }
+}
+
+/// The catalog embedded in this build
+pub fn embedded() -> &'static Catalog {
+    static CATALOG: OnceLock<Catalog> = OnceLock::new();
+    CATALOG.get_or_init(|| {
+        Catalog::parse(EMBEDDED_CATALOG).expect("embedded catalog is validated by build.rs")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fixture() -> Catalog {
+        let catalog = json!({
+            "schema_version": 1,
+            "catalog_version": 1,
+            "last_compatible": "5.4.0 (Beta6)",
+            "personas": [],
+            "versions": [
+                {
+                    "label": "5.4.0 (Beta6)",
+                    "url": "https://example.com/CapCut_5_4_0_1991_beta6_capcutpc_beta_creatortool.exe"
+                },
+                {
+                    "label": "5.4.0 (Beta5)",
+                    "url": "https://example.com/CapCut_5_4_0_1988_beta5_capcutpc_beta_creatortool.exe"
+                },
+                {
+                    "label": "3.9.0",
+                    "url": "https://example.com/CapCut_3_9_0_1459_capcutpc_0_creatortool.exe"
+                }
+            ]
+        });
+        Catalog::parse(&catalog.to_string()).unwrap()
+    }
+
+    fn found(catalog: &Catalog, name: &str) -> Option<String> {
+        let version = CapCutVersion::parse(name).unwrap();
+        catalog.find_build(&version).map(|e| e.label.clone())
+    }
+
+    #[test]
+    fn builds_match_by_release_and_build_number() {
+        let catalog = fixture();
+        let installer =
+            InstallerName::parse("CapCut_5_4_0_1991_beta6_capcutpc_beta_creatortool (1).exe")
+                .unwrap();
+        assert_eq!(
+            catalog
+                .find_build(&installer.version)
+                .map(|e| e.label.as_str()),
+            Some("5.4.0 (Beta6)")
+        );
+        // Folder names and PE versions carry no channel; the build decides
+        assert_eq!(found(&catalog, "5.4.0.1988"), Some("5.4.0 (Beta5)".into()));
+        assert_eq!(found(&catalog, "3.9.0.1459"), Some("3.9.0".into()));
+
+        assert_eq!(found(&catalog, "5.3.0.1991"), None);
+        assert_eq!(found(&catalog, "5.4.0.2000"), None);
+        // Labels have no build to match
+        assert_eq!(found(&catalog, "5.4.0 (Beta6)"), None);
+    }
+}
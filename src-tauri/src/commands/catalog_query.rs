@@ -0,0 +1,449 @@
+//! Catalog browsing
+//! Filters, searches, sorts and pages the version catalog, with facet counts for the filter UI
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::catalog::Catalog;
+use super::catalog_store;
+use super::scanner::{self, ArchiveVersion};
+use super::version::{CapCutVersion, ChannelKind};
+
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 500;
+
+/// Sort direction by real version order
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    Newest,
+    Oldest,
+}
+
+/// Filters for `query_catalog`; every field is optional
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CatalogQuery {
+    pub major: Option<u32>,
+    pub channel: Option<ChannelKind>,
+    pub risk_level: Option<String>,
+    /// Only versions CC Version Guard works with (true) or only newer ones (false)
+    pub compatible: Option<bool>,
+    /// Free text matched against labels and notes; every word must match
+    pub search: Option<String>,
+    pub sort: SortOrder,
+    /// 1-based page number
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+}
+
+/// A catalog version with the fields it can be filtered on
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogItem {
+    #[serde(flatten)]
+    pub archive: ArchiveVersion,
+    pub label: String,
+    pub channel: ChannelKind,
+    pub compatible: bool,
+}
+
+/// Number of matches for one facet value
+#[derive(Debug, Clone, Serialize)]
+pub struct FacetCount<T> {
+    pub value: T,
+    pub count: usize,
+}
+
+/// Match counts per filter value. Each facet applies every filter except its own,
+/// so the UI can show how many results picking another value would give.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CatalogFacets {
+    pub majors: Vec<FacetCount<u32>>,
+    pub channels: Vec<FacetCount<ChannelKind>>,
+    pub risk_levels: Vec<FacetCount<String>>,
+    pub compatible: Vec<FacetCount<bool>>,
+}
+
+/// One page of query results
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogPage {
+    pub items: Vec<CatalogItem>,
+    /// Matches across all pages
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+    pub total_pages: usize,
+    pub facets: CatalogFacets,
+}
+
+/// Filter dimensions, so facets can skip their own
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Facet {
+    Major,
+    Channel,
+    Risk,
+    Compatible,
+}
+
+/// A catalog version, parsed, with its browsable item
+pub(crate) struct Entry {
+    pub(crate) version: CapCutVersion,
+    /// Lowercased label and note
+    text: String,
+    pub(crate) item: CatalogItem,
+}
+
+impl CatalogQuery {
+    fn search_terms(&self) -> Vec<String> {
+        self.search
+            .as_deref()
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect()
+    }
+
+    fn matches(&self, entry: &Entry, terms: &[String], skip: Option<Facet>) -> bool {
+        let item = &entry.item;
+        let check = |facet: Facet, ok: bool| skip == Some(facet) || ok;
+
+        check(
+            Facet::Major,
+            self.major.is_none_or(|m| m == entry.version.major),
+        ) && check(
+            Facet::Channel,
+            self.channel.is_none_or(|c| c == item.channel),
+        ) && check(
+            Facet::Risk,
+            self.risk_level
+                .as_deref()
+                .is_none_or(|r| r.eq_ignore_ascii_case(&item.archive.risk_level)),
+        ) && check(
+            Facet::Compatible,
+            self.compatible.is_none_or(|c| c == item.compatible),
+        ) && terms.iter().all(|t| entry.text.contains(t.as_str()))
+    }
+}
+
+/// Browsable entries for every version in a catalog
+pub(crate) fn load_entries(catalog: &Catalog) -> Vec<Entry> {
+    catalog
+        .versions
+        .iter()
+        .filter_map(|entry| {
+            let version = CapCutVersion::parse(&entry.label)?;
+            let text = format!(
+                "{} {}",
+                entry.label,
+                entry.note.as_deref().unwrap_or_default()
+            )
+            .to_lowercase();
+            Some(Entry {
+                version,
+                text,
+                item: CatalogItem {
+                    archive: scanner::archive_version(catalog, entry, version),
+                    label: entry.label.clone(),
+                    channel: version.channel.kind(),
+                    compatible: catalog.is_compatible(&version),
+                },
+            })
+        })
+        .collect()
+}
+
+/// Count matching entries per facet value, ignoring the facet's own filter
+fn count<K: Ord, V>(
+    entries: &[Entry],
+    query: &CatalogQuery,
+    terms: &[String],
+    facet: Facet,
+    key: impl Fn(&Entry) -> K,
+    value: impl Fn(K) -> V,
+) -> Vec<FacetCount<V>> {
+    let mut counts = BTreeMap::new();
+    for entry in entries
+        .iter()
+        .filter(|e| query.matches(e, terms, Some(facet)))
+    {
+        *counts.entry(key(entry)).or_insert(0) += 1;
+    }
+    counts
+        .into_iter()
+        .map(|(k, count)| FacetCount {
+            value: value(k),
+            count,
+        })
+        .collect()
+}
+
+fn facets(entries: &[Entry], query: &CatalogQuery, terms: &[String]) -> CatalogFacets {
+    let mut majors = count(
+        entries,
+        query,
+        terms,
+        Facet::Major,
+        |e| e.version.major,
+        |m| m,
+    );
+    majors.reverse();
+
+    CatalogFacets {
+        majors,
+        channels: count(
+            entries,
+            query,
+            terms,
+            Facet::Channel,
+            |e| e.item.channel,
+            |c| c,
+        ),
+        risk_levels: count(
+            entries,
+            query,
+            terms,
+            Facet::Risk,
+            |e| e.item.archive.risk_level.clone(),
+            |r| r,
+        ),
+        compatible: count(
+            entries,
+            query,
+            terms,
+            Facet::Compatible,
+            |e| e.item.compatible,
+            |c| c,
+        ),
+    }
+}
+
+/// Filter, search, sort and page the catalog
+#[tauri::command]
+pub fn query_catalog(query: CatalogQuery) -> CatalogPage {
+    query_entries(&load_entries(&catalog_store::current()), &query)
+}
+
+fn query_entries(entries: &[Entry], query: &CatalogQuery) -> CatalogPage {
+    let terms = query.search_terms();
+
+    let mut matches: Vec<&Entry> = entries
+        .iter()
+        .filter(|e| query.matches(e, &terms, None))
+        .collect();
+    matches.sort_by(|a, b| match query.sort {
+        SortOrder::Newest => b.version.cmp(&a.version),
+        SortOrder::Oldest => a.version.cmp(&b.version),
+    });
+
+    let page_size = query
+        .page_size
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let total = matches.len();
+    let total_pages = total.div_ceil(page_size);
+    let page = query.page.unwrap_or(1).max(1);
+
+    CatalogPage {
+        items: matches
+            .into_iter()
+            .skip((page - 1) * page_size)
+            .take(page_size)
+            .map(|e| e.item.clone())
+            .collect(),
+        total,
+        page,
+        page_size,
+        total_pages,
+        facets: facets(entries, query, &terms),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{json_keys, scratch_local_app_data};
+    use serde_json::json;
+
+    #[test]
+    fn catalog_items_serialize_each_key_once() {
+        let (_guard, _) = scratch_local_app_data("catalog-query-keys");
+        let entries = load_entries(&catalog_store::current());
+        assert!(!entries.is_empty());
+
+        for entry in &entries {
+            let mut keys = json_keys(&entry.item);
+            assert!(keys.contains(&"channel".to_string()));
+            assert!(keys.contains(&"channel_label".to_string()));
+            let count = keys.len();
+            keys.sort();
+            keys.dedup();
+            assert_eq!(keys.len(), count, "duplicate keys in {:?}", keys);
+        }
+    }
+
+    fn fixture() -> Vec<Entry> {
+        let catalog = json!({
+            "schema_version": 1,
+            "catalog_version": 1,
+            "last_compatible": "5.4.0 (Beta6)",
+            "personas": [],
+            "risk_rules": [
+                { "id": "beta_build", "when": { "channel": ["beta"] }, "points": 2, "reason": "Beta build" },
+                { "id": "unsupported", "when": { "compatible": false }, "points": 4, "reason": "Unsupported" }
+            ],
+            "risk_thresholds": [
+                { "level": "Medium", "points": 2 },
+                { "level": "High", "points": 4 }
+            ],
+            "versions": [
+                { "label": "3.8.0", "url": "https://example.com/CapCut_3_8_0_1400_capcutpc_0.exe" },
+                {
+                    "label": "3.9.0",
+                    "url": "https://example.com/CapCut_3_9_0_1459_capcutpc_0.exe",
+                    "note": "Free captions, stable"
+                },
+                {
+                    "label": "4.1.0 (Beta2)",
+                    "url": "https://example.com/CapCut_4_1_0_1600_beta2_capcutpc_beta.exe"
+                },
+                {
+                    "label": "4.2.0",
+                    "url": "https://example.com/CapCut_4_2_0_1650_capcutpc_0.exe",
+                    "note": "Last version with free auto captions"
+                },
+                {
+                    "label": "5.4.0 (Beta6)",
+                    "url": "https://example.com/CapCut_5_4_0_1991_beta6_capcutpc_beta.exe"
+                },
+                { "label": "5.5.0", "url": "https://example.com/CapCut_5_5_0_2100_capcutpc_0.exe" }
+            ]
+        });
+        load_entries(&Catalog::parse(&catalog.to_string()).unwrap())
+    }
+
+    fn labels(page: &CatalogPage) -> Vec<&str> {
+        page.items.iter().map(|i| i.label.as_str()).collect()
+    }
+
+    fn query(build: impl FnOnce(&mut CatalogQuery)) -> CatalogQuery {
+        let mut query = CatalogQuery::default();
+        build(&mut query);
+        query
+    }
+
+    fn pairs<T: Clone>(facets: &[FacetCount<T>]) -> Vec<(T, usize)> {
+        facets.iter().map(|f| (f.value.clone(), f.count)).collect()
+    }
+
+    #[test]
+    fn filters_combine() {
+        let entries = fixture();
+        let run = |q: CatalogQuery| labels(&query_entries(&entries, &q)).join(", ");
+
+        assert_eq!(run(query(|q| q.major = Some(4))), "4.2.0, 4.1.0 (Beta2)");
+        assert_eq!(
+            run(query(|q| q.channel = Some(ChannelKind::Beta))),
+            "5.4.0 (Beta6), 4.1.0 (Beta2)"
+        );
+        assert_eq!(run(query(|q| q.compatible = Some(false))), "5.5.0");
+        assert_eq!(
+            run(query(|q| q.risk_level = Some("medium".into()))),
+            "5.4.0 (Beta6), 4.1.0 (Beta2)"
+        );
+        assert_eq!(
+            run(query(|q| {
+                q.major = Some(5);
+                q.compatible = Some(true);
+            })),
+            "5.4.0 (Beta6)"
+        );
+    }
+
+    #[test]
+    fn every_search_word_must_match_label_or_note() {
+        let entries = fixture();
+        let search = |text: &str| {
+            let page = query_entries(&entries, &query(|q| q.search = Some(text.into())));
+            labels(&page).join(", ")
+        };
+
+        assert_eq!(search("free captions"), "4.2.0, 3.9.0");
+        assert_eq!(search("  STABLE   free "), "3.9.0");
+        assert_eq!(search("beta6"), "5.4.0 (Beta6)");
+        assert_eq!(search("captions beta"), "");
+        assert_eq!(search(""), search(" "));
+    }
+
+    #[test]
+    fn sorts_by_version_order() {
+        let entries = fixture();
+        let newest = query_entries(&entries, &CatalogQuery::default());
+        assert_eq!(
+            labels(&newest),
+            [
+                "5.5.0",
+                "5.4.0 (Beta6)",
+                "4.2.0",
+                "4.1.0 (Beta2)",
+                "3.9.0",
+                "3.8.0"
+            ]
+        );
+        let oldest = query_entries(&entries, &query(|q| q.sort = SortOrder::Oldest));
+        let mut reversed = labels(&newest);
+        reversed.reverse();
+        assert_eq!(labels(&oldest), reversed);
+    }
+
+    #[test]
+    fn pages_are_clamped() {
+        let entries = fixture();
+        let page = |number: Option<usize>, size: Option<usize>| {
+            query_entries(
+                &entries,
+                &query(|q| {
+                    q.page = number;
+                    q.page_size = size;
+                }),
+            )
+        };
+
+        let second = page(Some(2), Some(4));
+        assert_eq!(labels(&second), ["3.9.0", "3.8.0"]);
+        assert_eq!((second.total, second.total_pages), (6, 2));
+
+        let tiny = page(Some(0), Some(0));
+        assert_eq!((tiny.page, tiny.page_size, tiny.total_pages), (1, 1, 6));
+        assert_eq!(labels(&tiny), ["5.5.0"]);
+
+        let huge = page(None, Some(100_000));
+        assert_eq!((huge.page_size, huge.items.len()), (MAX_PAGE_SIZE, 6));
+        assert_eq!(page(None, None).page_size, DEFAULT_PAGE_SIZE);
+
+        let past_end = page(Some(9), Some(4));
+        assert!(past_end.items.is_empty());
+        assert_eq!(past_end.total, 6);
+    }
+
+    #[test]
+    fn facets_skip_their_own_filter() {
+        let entries = fixture();
+        let page = query_entries(
+            &entries,
+            &query(|q| {
+                q.major = Some(4);
+                q.channel = Some(ChannelKind::Beta);
+            }),
+        );
+        assert_eq!(labels(&page), ["4.1.0 (Beta2)"]);
+
+        assert_eq!(pairs(&page.facets.majors), [(5, 1), (4, 1)]);
+        assert_eq!(
+            pairs(&page.facets.channels),
+            [(ChannelKind::Stable, 1), (ChannelKind::Beta, 1)]
+        );
+        assert_eq!(pairs(&page.facets.risk_levels), [("Medium".to_string(), 1)]);
+        assert_eq!(pairs(&page.facets.compatible), [(true, 1)]);
+    }
+}
@@ -0,0 +1,524 @@
+//! Catalog sources: signed updates, local cache and user overrides
+//! Loads the newest verified catalog, falling back to the one embedded in the binary
+
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use super::catalog::{self, Catalog, CatalogOverrides};
+use super::paths;
+
+/// Release keys a published catalog may be signed with (validated by build.rs)
+#[cfg(not(test))]
+const CATALOG_KEYS: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/data/catalog_keys.txt"
+));
+
+/// Public half of the fixed key tests sign with (`tests::signing_key`)
+#[cfg(test)]
+const CATALOG_KEYS: &str = "ea4a6c63e29c520abef5507b132ec5f9954776aebebe7b92421eea691446d22c";
+
+/// Suffix of the detached signature next to a catalog
+const SIGNATURE_SUFFIX: &str = ".sig";
+
+/// Largest catalog we are willing to download
+const MAX_CATALOG_SIZE: u64 = 8 * 1024 * 1024;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Persisted catalog settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CatalogSettings {
+    /// File path or http(s) URL of the published catalog; its signature lives at the same place + ".sig"
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+/// Where the catalog in use came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogOrigin {
+    Embedded,
+    Cached,
+}
+
+/// The catalog in use and how it was assembled
+#[derive(Debug, Clone)]
+pub struct LoadedCatalog {
+    pub catalog: Arc<Catalog>,
+    pub origin: CatalogOrigin,
+    pub overrides_applied: bool,
+    /// Why the overrides file was ignored
+    pub override_error: Option<String>,
+}
+
+/// Summary for the settings screen
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogInfo {
+    pub origin: CatalogOrigin,
+    pub catalog_version: u64,
+    pub embedded_version: u64,
+    pub version_count: usize,
+    pub persona_count: usize,
+    pub source: Option<String>,
+    pub overrides_path: Option<String>,
+    pub overrides_applied: bool,
+    pub override_error: Option<String>,
+    /// This build has a release key, so signed catalog updates can be used
+    pub updates_enabled: bool,
+}
+
+/// Result of fetching a catalog from the configured source
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogUpdateResult {
+    pub success: bool,
+    /// A newer catalog was verified and cached
+    pub updated: bool,
+    pub error: Option<String>,
+    pub logs: Vec<String>,
+    /// Version of the catalog now in use (before overrides)
+    pub catalog_version: u64,
+}
+
+fn get_catalog_dir() -> Option<PathBuf> {
+    paths::get_guard_data_dir().map(|p| p.join("catalog"))
+}
+
+fn get_settings_path() -> Option<PathBuf> {
+    get_catalog_dir().map(|p| p.join("settings.json"))
+}
+
+fn get_cache_path() -> Option<PathBuf> {
+    get_catalog_dir().map(|p| p.join("catalog.json"))
+}
+
+/// User-editable overrides merged on top of the catalog
+pub fn get_overrides_path() -> Option<PathBuf> {
+    get_catalog_dir().map(|p| p.join("overrides.json"))
+}
+
+fn signature_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(SIGNATURE_SUFFIX);
+    PathBuf::from(name)
+}
+
+fn load_settings() -> CatalogSettings {
+    get_settings_path()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+/// Release keys embedded in this build
+fn release_keys() -> Result<Vec<VerifyingKey>, String> {
+    let keys = catalog::parse_signing_keys(CATALOG_KEYS)?;
+    if keys.is_empty() {
+        return Err(
+            "This build has no catalog signing key - signed catalog updates are disabled"
+                .to_string(),
+        );
+    }
+    keys.iter()
+        .map(|key| VerifyingKey::from_bytes(key).map_err(|e| e.to_string()))
+        .collect()
+}
+
+/// Check a hex-encoded detached signature over the exact catalog bytes
+fn verify_signature(content: &[u8], signature: &str) -> Result<(), String> {
+    let keys = release_keys()?;
+    let signature: [u8; 64] = catalog::decode_hex(signature)
+        .and_then(|s| s.try_into().ok())
+        .ok_or("Catalog signature is not a hex-encoded ed25519 signature")?;
+    let signature = Signature::from_bytes(&signature);
+    if keys
+        .iter()
+        .any(|key| key.verify_strict(content, &signature).is_ok())
+    {
+        Ok(())
+    } else {
+        Err("Catalog signature does not match any release key".to_string())
+    }
+}
+
+/// Parse a catalog only if its signature verifies
+fn parse_signed(content: &[u8], signature: &str) -> Result<Catalog, String> {
+    verify_signature(content, signature)?;
+    let text = std::str::from_utf8(content).map_err(|_| "Catalog is not UTF-8 text")?;
+    Catalog::parse(text)
+}
+
+/// The verified cached catalog, if any
+fn load_cached() -> Option<Catalog> {
+    let path = get_cache_path()?;
+    let content = fs::read(&path).ok()?;
+    let signature = fs::read_to_string(signature_path(&path)).ok()?;
+    parse_signed(&content, &signature).ok()
+}
+
+fn load_overrides() -> Result<Option<CatalogOverrides>, String> {
+    let Some(path) = get_overrides_path().filter(|p| p.exists()) else {
+        return Ok(None);
+    };
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("Invalid overrides.json: {}", e))
+}
+
+/// Newest verified catalog (cached or embedded), without overrides
+fn base_catalog() -> (Catalog, CatalogOrigin) {
+    let embedded = catalog::embedded();
+    match load_cached() {
+        Some(cached) if cached.catalog_version > embedded.catalog_version => {
+            (cached, CatalogOrigin::Cached)
+        }
+        _ => (embedded.clone(), CatalogOrigin::Embedded),
+    }
+}
+
+/// Load the catalog in use from disk: newest verified catalog plus valid overrides
+fn load() -> LoadedCatalog {
+    let (base, origin) = base_catalog();
+    let merged = load_overrides().and_then(|o| match o {
+        Some(overrides) => base.merge(&overrides).map(Some),
+        None => Ok(None),
+    });
+
+    match merged {
+        Ok(Some(catalog)) => LoadedCatalog {
+            catalog: Arc::new(catalog),
+            origin,
+            overrides_applied: true,
+            override_error: None,
+        },
+        Ok(None) => LoadedCatalog {
+            catalog: Arc::new(base),
+            origin,
+            overrides_applied: false,
+            override_error: None,
+        },
+        Err(e) => LoadedCatalog {
+            catalog: Arc::new(base),
+            origin,
+            overrides_applied: false,
+            override_error: Some(e),
+        },
+    }
+}
+
+/// Paths, modification times and sizes of the files a loaded catalog was built from
+type SourceStamp = Vec<(Option<PathBuf>, Option<(SystemTime, u64)>)>;
+
+/// The loaded catalog, reused until its source files change or an update is cached
+static LOADED: Mutex<Option<(SourceStamp, Arc<LoadedCatalog>)>> = Mutex::new(None);
+
+fn source_stamp() -> SourceStamp {
+    let cache = get_cache_path();
+    [
+        cache.clone(),
+        cache.as_deref().map(signature_path),
+        get_overrides_path(),
+    ]
+    .into_iter()
+    .map(|path| {
+        let meta = path.as_ref().and_then(|p| fs::metadata(p).ok());
+        let stamp = meta.and_then(|m| Some((m.modified().ok()?, m.len())));
+        (path, stamp)
+    })
+    .collect()
+}
+
+/// The catalog in use and how it was assembled.
+/// Verified once and memoized; a changed cache or overrides file is picked up on the next call.
+pub fn loaded() -> Arc<LoadedCatalog> {
+    let stamp = source_stamp();
+    let mut memo = LOADED.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((loaded_stamp, loaded)) = memo.as_ref() {
+        if *loaded_stamp == stamp {
+            return loaded.clone();
+        }
+    }
+    let loaded = Arc::new(load());
+    *memo = Some((stamp, loaded.clone()));
+    loaded
+}
+
+/// Forget the memoized catalog so the next call reloads it
+fn invalidate() {
+    *LOADED.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// The catalog in use
+pub fn current() -> Arc<Catalog> {
+    loaded().catalog.clone()
+}
+
+/// Read a file path or http(s) URL
+fn fetch(source: &str) -> Result<Vec<u8>, String> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        let response = ureq::get(source)
+            .timeout(FETCH_TIMEOUT)
+            .call()
+            .map_err(|e| format!("Could not fetch {}: {}", source, e))?;
+        let mut content = Vec::new();
+        response
+            .into_reader()
+            .take(MAX_CATALOG_SIZE + 1)
+            .read_to_end(&mut content)
+            .map_err(|e| format!("Could not read {}: {}", source, e))?;
+        if content.len() as u64 > MAX_CATALOG_SIZE {
+            return Err(format!("{} is too large to be a catalog", source));
+        }
+        Ok(content)
+    } else {
+        let path = source.strip_prefix("file://").unwrap_or(source);
+        fs::read(path).map_err(|e| format!("Could not read {}: {}", path, e))
+    }
+}
+
+/// Write a file via a temporary sibling so a crash never leaves half a catalog
+fn write_atomic(path: &Path, content: &[u8]) -> Result<(), String> {
+    let temp = path.with_extension("tmp");
+    fs::write(&temp, content).map_err(|e| e.to_string())?;
+    fs::rename(&temp, path).map_err(|e| e.to_string())
+}
+
+fn update_from(source: &str, logs: &mut Vec<String>) -> Result<bool, String> {
+    release_keys()?;
+    logs.push(format!("Fetching catalog from {}...", source));
+    let content = fetch(source)?;
+    let signature = fetch(&format!("{}{}", source, SIGNATURE_SUFFIX))?;
+    let signature = String::from_utf8_lossy(&signature).to_string();
+
+    let catalog = parse_signed(&content, &signature)?;
+    logs.push(format!(
+        "[OK] Signature verified - catalog version {} ({} versions)",
+        catalog.catalog_version,
+        catalog.versions.len()
+    ));
+
+    let (current, _) = base_catalog();
+    if catalog.catalog_version <= current.catalog_version {
+        logs.push(format!(
+            "Catalog version {} is already in use - nothing to update",
+            current.catalog_version
+        ));
+        return Ok(false);
+    }
+
+    let cache_path = get_cache_path().ok_or("Could not determine data directory")?;
+    if let Some(parent) = cache_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    // Signature first: a catalog without a matching signature is ignored on load
+    write_atomic(&signature_path(&cache_path), signature.as_bytes())?;
+    write_atomic(&cache_path, &content)?;
+    invalidate();
+    logs.push(format!(
+        "[OK] Updated catalog {} -> {}",
+        current.catalog_version, catalog.catalog_version
+    ));
+    Ok(true)
+}
+
+/// Fetch the catalog from the configured source and cache it if it is newer and correctly signed
+#[tauri::command]
+pub fn update_catalog() -> CatalogUpdateResult {
+    let mut logs = Vec::new();
+    let result = match load_settings().source {
+        Some(source) => update_from(&source, &mut logs),
+        None => Err("No catalog source configured".to_string()),
+    };
+    if let Err(e) = &result {
+        logs.push(format!("[!] {}", e));
+    }
+
+    CatalogUpdateResult {
+        success: result.is_ok(),
+        updated: result.as_ref().is_ok_and(|u| *u),
+        error: result.err(),
+        logs,
+        catalog_version: base_catalog().0.catalog_version,
+    }
+}
+
+/// Describe the catalog in use
+#[tauri::command]
+pub fn get_catalog_info() -> CatalogInfo {
+    let loaded = loaded();
+    CatalogInfo {
+        origin: loaded.origin,
+        catalog_version: loaded.catalog.catalog_version,
+        embedded_version: catalog::embedded().catalog_version,
+        version_count: loaded.catalog.versions.len(),
+        persona_count: loaded.catalog.personas.len(),
+        source: load_settings().source,
+        overrides_path: get_overrides_path().map(|p| p.to_string_lossy().to_string()),
+        overrides_applied: loaded.overrides_applied,
+        override_error: loaded.override_error.clone(),
+        updates_enabled: release_keys().is_ok(),
+    }
+}
+
+/// Get catalog settings
+#[tauri::command]
+pub fn get_catalog_settings() -> CatalogSettings {
+    load_settings()
+}
+
+/// Update catalog settings
+#[tauri::command]
+pub fn set_catalog_settings(settings: CatalogSettings) -> Result<(), String> {
+    let path = get_settings_path().ok_or("Could not determine settings directory")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    fs::write(path, content).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{scratch_local_app_data, Response, TestServer};
+    use ed25519_dalek::{Signer, SigningKey};
+
+    /// Signs for the key in the test build's CATALOG_KEYS
+    fn signing_key() -> SigningKey {
+        SigningKey::from_bytes(&[7; 32])
+    }
+
+    fn sign(key: &SigningKey, content: &[u8]) -> String {
+        key.sign(content)
+            .to_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// The embedded catalog republished as `catalog_version`, with its signature
+    fn published(catalog_version: u64) -> (Vec<u8>, String) {
+        let mut catalog = catalog::embedded().clone();
+        catalog.catalog_version = catalog_version;
+        let content = serde_json::to_vec_pretty(&catalog).unwrap();
+        let signature = sign(&signing_key(), &content);
+        (content, signature)
+    }
+
+    fn write_cache(content: &[u8], signature: &str) {
+        let path = get_cache_path().unwrap();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        fs::write(signature_path(&path), signature).unwrap();
+    }
+
+    fn embedded_version() -> u64 {
+        catalog::embedded().catalog_version
+    }
+
+    #[test]
+    fn only_release_key_signatures_verify() {
+        let (content, signature) = published(embedded_version() + 1);
+        assert!(verify_signature(&content, &signature).is_ok());
+
+        let mut tampered = content.clone();
+        tampered[10] ^= 1;
+        assert!(verify_signature(&tampered, &signature).is_err());
+
+        let other = sign(&SigningKey::from_bytes(&[8; 32]), &content);
+        assert!(verify_signature(&content, &other).is_err());
+        assert!(verify_signature(&content, "not hex").is_err());
+    }
+
+    #[test]
+    fn update_caches_a_newer_signed_catalog_from_http() {
+        let (_guard, _) = scratch_local_app_data("catalog-update");
+        let next = embedded_version() + 1;
+        let (content, signature) = published(next);
+        let server = TestServer::start(move |request, _| match request.path.as_str() {
+            "/catalog.json" => Response::ok(&content),
+            "/catalog.json.sig" => Response::ok(signature.as_bytes()),
+            _ => Response::status(404),
+        });
+        assert_eq!(current().catalog_version, embedded_version());
+
+        let mut logs = Vec::new();
+        assert_eq!(
+            update_from(&server.url("/catalog.json"), &mut logs),
+            Ok(true)
+        );
+        let loaded = loaded();
+        assert_eq!(loaded.origin, CatalogOrigin::Cached);
+        assert_eq!(loaded.catalog.catalog_version, next);
+
+        // Same version again: nothing to do
+        assert_eq!(
+            update_from(&server.url("/catalog.json"), &mut logs),
+            Ok(false)
+        );
+        assert_eq!(server.requests().len(), 4);
+    }
+
+    #[test]
+    fn update_rejects_a_catalog_signed_with_another_key() {
+        let (_guard, _) = scratch_local_app_data("catalog-forged");
+        let (content, _) = published(embedded_version() + 1);
+        let forged = sign(&SigningKey::from_bytes(&[8; 32]), &content);
+        let server = TestServer::start(move |request, _| {
+            if request.path.ends_with(".sig") {
+                Response::ok(forged.as_bytes())
+            } else {
+                Response::ok(&content)
+            }
+        });
+
+        assert!(update_from(&server.url("/catalog.json"), &mut Vec::new()).is_err());
+        assert!(!get_cache_path().unwrap().exists());
+        assert_eq!(loaded().origin, CatalogOrigin::Embedded);
+    }
+
+    #[test]
+    fn invalid_or_older_cache_falls_back_to_embedded() {
+        let (_guard, _) = scratch_local_app_data("catalog-fallback");
+        let (content, signature) = published(embedded_version() + 1);
+
+        write_cache(&content, "00");
+        assert_eq!(loaded().origin, CatalogOrigin::Embedded);
+
+        write_cache(&content, &signature);
+        assert_eq!(loaded().origin, CatalogOrigin::Cached);
+
+        let (older, older_signature) = published(embedded_version().saturating_sub(1));
+        write_cache(&older, &older_signature);
+        let loaded = loaded();
+        assert_eq!(loaded.origin, CatalogOrigin::Embedded);
+        assert_eq!(loaded.catalog.catalog_version, embedded_version());
+    }
+
+    #[test]
+    fn overrides_are_merged_and_invalid_ones_reported() {
+        let (_guard, _) = scratch_local_app_data("catalog-overrides");
+        let embedded = catalog::embedded();
+        let oldest = embedded.versions.last().unwrap().label.clone();
+        let overrides = get_overrides_path().unwrap();
+        fs::create_dir_all(overrides.parent().unwrap()).unwrap();
+
+        fs::write(&overrides, format!("{{ \"remove\": [\"{}\"] }}", oldest)).unwrap();
+        let merged = loaded();
+        assert!(merged.overrides_applied);
+        assert_eq!(merged.catalog.versions.len(), embedded.versions.len() - 1);
+        assert!(merged.catalog.versions.iter().all(|v| v.label != oldest));
+
+        fs::write(&overrides, "{ \"remove\": [").unwrap();
+        let broken = loaded();
+        assert!(!broken.overrides_applied);
+        assert!(broken.override_error.is_some());
+        assert_eq!(broken.catalog.versions.len(), embedded.versions.len());
+    }
+}
@@ -1,10 +1,11 @@
 //! Cache cleaning functionality
 //! Migrated from original eframe/egui main.rs
 
-use std::fs;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+use super::fsops::{ChangePlan, FsOps};
+
 /// Get cache directories for CapCut
 fn get_cache_dirs(capcut_root: &Path) -> Vec<PathBuf> {
     let user_data = capcut_root.join("User Data");
@@ -26,21 +27,6 @@ fn calculate_dir_size(path: &Path) -> u64 {
         .sum()
 }
 
-/// Unset readonly attribute recursively
-fn unset_readonly_recursive(path: &Path) -> Result<(), String> {
-    for entry in WalkDir::new(path).into_iter().filter_map(|e| e.ok()) {
-        let p = entry.path();
-        if let Ok(meta) = fs::metadata(p) {
-            let mut perms = meta.permissions();
-            if perms.readonly() {
-                perms.set_readonly(false);
-                fs::set_permissions(p, perms).ok();
-            }
-        }
-    }
-    Ok(())
-}
-
 /// Calculate total cache size in MB
 #[tauri::command]
 pub fn calculate_cache_size() -> f64 {
@@ -70,20 +56,41 @@ pub struct CacheCleanResult {
 /// Clean cache directories
 #[tauri::command]
 pub fn clean_cache() -> CacheCleanResult {
-    let capcut_root = match std::env::var("LOCALAPPDATA") {
-        Ok(p) => PathBuf::from(p).join("CapCut"),
-        Err(_) => {
-            return CacheCleanResult {
+    let mut logs: Vec<String> = Vec::new();
+    match clean_cache_with(&mut FsOps::execute(), &mut logs) {
+        Ok(cleaned_mb) => CacheCleanResult {
+            success: true,
+            cleaned_mb,
+            logs,
+        },
+        Err(e) => {
+            logs.push(e);
+            CacheCleanResult {
                 success: false,
                 cleaned_mb: 0.0,
-                logs: vec!["Failed to get LOCALAPPDATA".to_string()],
+                logs,
             }
         }
-    };
+    }
+}
+
+/// Plan cache cleaning without touching disk
+#[tauri::command]
+pub fn plan_clean_cache() -> ChangePlan {
+    let mut ops = FsOps::plan();
+    let mut logs: Vec<String> = Vec::new();
+    let result = clean_cache_with(&mut ops, &mut logs).map(|_| ());
+    ops.into_plan(result, logs)
+}
+
+/// Remove cache directories, returning the MB cleaned
+pub(crate) fn clean_cache_with(ops: &mut FsOps, logs: &mut Vec<String>) -> Result<f64, String> {
+    let capcut_root = std::env::var("LOCALAPPDATA")
+        .map(|p| PathBuf::from(p).join("CapCut"))
+        .map_err(|_| "Failed to get LOCALAPPDATA".to_string())?;
 
     let dirs = get_cache_dirs(&capcut_root);
     let mut total_cleaned: u64 = 0;
-    let mut logs: Vec<String> = Vec::new();
 
     for dir in dirs {
         if dir.exists() {
@@ -95,11 +102,7 @@ pub fn clean_cache() -> CacheCleanResult {
                 size as f64 / (1024.0 * 1024.0)
             ));
 
-            if let Err(e) = unset_readonly_recursive(&dir) {
-                logs.push(format!("[!] Warning: {}", e));
-            }
-
-            if let Err(e) = fs::remove_dir_all(&dir) {
+            if let Err(e) = ops.remove(&dir) {
                 logs.push(format!("[!] Failed to clean {}: {}", name, e));
             } else {
                 total_cleaned += size;
@@ -110,9 +113,5 @@ pub fn clean_cache() -> CacheCleanResult {
     let cleaned_mb = total_cleaned as f64 / (1024.0 * 1024.0);
     logs.push(format!("[OK] Cleaned {:.1} MB of cache", cleaned_mb));
 
-    CacheCleanResult {
-        success: true,
-        cleaned_mb,
-        logs,
-    }
+    Ok(cleaned_mb)
 }
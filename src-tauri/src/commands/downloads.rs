@@ -0,0 +1,809 @@
+//! Installer downloads
+//! Fetches catalog installers into the Guard data folder, resuming partial files with HTTP Range
+//! and verifying size and SHA-256 before an installer is usable
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use super::catalog::InstallerSource;
+use super::catalog_store;
+use super::installers;
+use super::integrity::{self, Verification, VerifiedInstaller};
+use super::mirrors;
+use super::paths;
+
+/// Event emitted to the frontend as downloads progress
+pub const DOWNLOAD_EVENT: &str = "download-progress";
+
+/// Suffix of a download that has not finished yet
+const PARTIAL_SUFFIX: &str = ".part";
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(20);
+/// A stalled connection is dropped and retried after this long without data
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Minimum time between progress events
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How often cancellation is checked while waiting to retry
+const CANCEL_POLL: Duration = Duration::from_millis(100);
+
+/// How failed attempts are retried
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Attempts in a row without receiving any data before giving up
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before the given retry (1-based), doubling each time
+    fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(1 << retry.saturating_sub(1).min(16))
+            .min(self.max_backoff)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadState {
+    Downloading,
+    /// Checking size and SHA-256 before the installer is usable
+    Verifying,
+    /// Waiting to retry after a failed attempt
+    Retrying,
+    Completed,
+    Failed,
+    /// Stopped by the user; the partial file is kept for resuming
+    Cancelled,
+}
+
+/// Payload of `DOWNLOAD_EVENT`
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadProgress {
+    /// Installer file name, unique per download
+    pub id: String,
+    pub url: String,
+    pub state: DownloadState,
+    pub downloaded: u64,
+    /// Unknown until the server reports a length
+    pub total: Option<u64>,
+    /// 1-based attempt number
+    pub attempt: u32,
+    /// Final file, once completed and verified
+    pub path: Option<String>,
+    pub sha256: Option<String>,
+    /// What the hash was checked against, once completed
+    pub verification: Option<Verification>,
+    pub error: Option<String>,
+}
+
+/// A file in the downloads folder
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadedFile {
+    pub id: String,
+    pub path: String,
+    pub size: u64,
+    /// False for partial files waiting to be resumed
+    pub complete: bool,
+    /// A download for this file is running
+    pub active: bool,
+}
+
+/// Why an attempt stopped
+enum Failure {
+    /// Worth trying again (network errors, server errors, truncated bodies)
+    Transient(String),
+    /// Retrying this URL will not help (missing file, access denied)
+    Fatal(String),
+    /// The partial file cannot be written; no URL will do better
+    Disk(String),
+    Cancelled,
+}
+
+/// Cancellation flags of running downloads, by id
+fn active() -> &'static Mutex<HashMap<String, Arc<AtomicBool>>> {
+    static ACTIVE: OnceLock<Mutex<HashMap<String, Arc<AtomicBool>>>> = OnceLock::new();
+    ACTIVE.get_or_init(Default::default)
+}
+
+/// Folder downloads land in; also the installer library
+pub fn get_downloads_dir() -> Option<PathBuf> {
+    paths::get_guard_data_dir().map(|p| p.join("downloads"))
+}
+
+fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(PARTIAL_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// File name at the end of a download URL
+pub(crate) fn file_name_from_url(url: &str) -> Result<String, String> {
+    let name = url
+        .split(['?', '#'])
+        .next()
+        .and_then(|u| u.rsplit('/').next())
+        .unwrap_or_default();
+    if name.is_empty() || name.starts_with('.') || name.contains(['\\', ':']) {
+        return Err(format!("{} does not name a file", url));
+    }
+    Ok(name.to_string())
+}
+
+/// Start offset and total size from a `Content-Range: bytes a-b/total` header
+fn content_range(header: Option<&str>) -> (Option<u64>, Option<u64>) {
+    let Some(range) = header.and_then(|h| h.trim().strip_prefix("bytes ")) else {
+        return (None, None);
+    };
+    let (span, total) = range.split_once('/').unwrap_or((range, "*"));
+    let start = span.split('-').next().and_then(|s| s.parse().ok());
+    (start, total.parse().ok())
+}
+
+/// Sleep, waking early if cancelled
+fn wait(duration: Duration, cancel: &AtomicBool) -> bool {
+    let deadline = Instant::now() + duration;
+    while Instant::now() < deadline {
+        if cancel.load(Ordering::Relaxed) {
+            return false;
+        }
+        std::thread::sleep(CANCEL_POLL.min(deadline - Instant::now()));
+    }
+    !cancel.load(Ordering::Relaxed)
+}
+
+/// Tracks one download and throttles its progress reports
+struct Transfer<'a> {
+    progress: DownloadProgress,
+    /// URL the partial file was started from; unknown for one left by an earlier session
+    part_source: Option<String>,
+    last_report: Option<Instant>,
+    report: &'a mut dyn FnMut(&DownloadProgress),
+}
+
+impl Transfer<'_> {
+    fn send(&mut self, force: bool) {
+        let due = self
+            .last_report
+            .is_none_or(|t| t.elapsed() >= PROGRESS_INTERVAL);
+        if force || due {
+            self.last_report = Some(Instant::now());
+            (self.report)(&self.progress);
+        }
+    }
+
+    fn set_state(&mut self, state: DownloadState) {
+        self.progress.state = state;
+        self.send(true);
+    }
+}
+
+/// One request: resume from the partial file if there is one
+fn attempt(
+    agent: &ureq::Agent,
+    part: &Path,
+    cancel: &AtomicBool,
+    transfer: &mut Transfer,
+) -> Result<(), Failure> {
+    let url = transfer.progress.url.clone();
+    let offset = fs::metadata(part).map(|m| m.len()).unwrap_or(0);
+    let mut request = agent.get(&url);
+    if offset > 0 {
+        request = request.set("Range", &format!("bytes={}-", offset));
+    }
+
+    let response = match request.call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(416, response)) => {
+            // Nothing left past our offset: either the partial file is already whole,
+            // or it is longer than the file on the server and must start over
+            let (_, total) = content_range(response.header("Content-Range"));
+            if total == Some(offset) {
+                transfer.progress.downloaded = offset;
+                transfer.progress.total = total;
+                return Ok(());
+            }
+            fs::remove_file(part).map_err(|e| Failure::Disk(e.to_string()))?;
+            return Err(Failure::Transient(
+                "Partial file does not match the server copy - restarting".to_string(),
+            ));
+        }
+        Err(ureq::Error::Status(code, _)) if code == 408 || code == 429 || code >= 500 => {
+            return Err(Failure::Transient(format!("Server returned HTTP {}", code)));
+        }
+        Err(ureq::Error::Status(code, _)) => {
+            return Err(Failure::Fatal(format!("Server returned HTTP {}", code)));
+        }
+        Err(e) => return Err(Failure::Transient(e.to_string())),
+    };
+
+    let (start, range_total) = content_range(response.header("Content-Range"));
+    let length: Option<u64> = response
+        .header("Content-Length")
+        .and_then(|l| l.parse().ok());
+    let resumed = response.status() == 206 && offset > 0 && start == Some(offset);
+    let mut file = if resumed {
+        transfer.progress.downloaded = offset;
+        transfer.progress.total = range_total.or(length.map(|l| l + offset));
+        OpenOptions::new().append(true).open(part)
+    } else {
+        // The server ignored the range (or answered a different one): start over
+        transfer.progress.downloaded = 0;
+        transfer.progress.total = if response.status() == 206 {
+            range_total
+        } else {
+            length
+        };
+        if response.status() == 206 && start != Some(0) {
+            fs::remove_file(part).ok();
+            return Err(Failure::Transient(
+                "Server answered an unexpected range".to_string(),
+            ));
+        }
+        transfer.part_source = Some(url.clone());
+        File::create(part)
+    }
+    .map_err(|e| Failure::Disk(format!("Could not write {}: {}", part.display(), e)))?;
+    transfer.set_state(DownloadState::Downloading);
+
+    let mut reader = response.into_reader();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(Failure::Cancelled);
+        }
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(Failure::Transient(e.to_string())),
+        };
+        file.write_all(&buffer[..read])
+            .map_err(|e| Failure::Disk(format!("Could not write {}: {}", part.display(), e)))?;
+        transfer.progress.downloaded += read as u64;
+        transfer.send(false);
+    }
+    file.sync_all()
+        .map_err(|e| Failure::Disk(format!("Could not write {}: {}", part.display(), e)))?;
+
+    match transfer.progress.total {
+        Some(total) if transfer.progress.downloaded < total => Err(Failure::Transient(format!(
+            "Connection closed after {} of {} bytes",
+            transfer.progress.downloaded, total
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Why fetching from one URL stopped
+enum Stop {
+    Cancelled,
+    /// This URL failed; another may work
+    Failed(String),
+    /// Nothing can be written locally
+    Disk(String),
+}
+
+/// Fetch the partial file to completion from the current URL, retrying transient failures
+fn fetch(
+    agent: &ureq::Agent,
+    part: &Path,
+    cancel: &AtomicBool,
+    policy: RetryPolicy,
+    transfer: &mut Transfer,
+) -> Result<(), Stop> {
+    // Failed attempts in a row that received nothing
+    let mut failures = 0;
+    loop {
+        transfer.progress.attempt += 1;
+        let before = fs::metadata(part).map(|m| m.len()).unwrap_or(0);
+        let error = match attempt(agent, part, cancel, transfer) {
+            Ok(()) => return Ok(()),
+            Err(Failure::Cancelled) => return Err(Stop::Cancelled),
+            Err(Failure::Fatal(e)) => return Err(Stop::Failed(e)),
+            Err(Failure::Disk(e)) => return Err(Stop::Disk(e)),
+            Err(Failure::Transient(e)) => e,
+        };
+
+        let after = fs::metadata(part).map(|m| m.len()).unwrap_or(0);
+        failures = if after > before { 1 } else { failures + 1 };
+        if failures >= policy.max_attempts {
+            return Err(Stop::Failed(error));
+        }
+        transfer.progress.error = Some(error);
+        transfer.set_state(DownloadState::Retrying);
+        if !wait(policy.backoff(failures), cancel) {
+            return Err(Stop::Cancelled);
+        }
+        transfer.progress.error = None;
+    }
+}
+
+/// Download an installer to `dest` from the first of `source.urls` that delivers a verified copy,
+/// resuming `dest.part` if present and retrying each URL with backoff.
+/// The file only reaches `dest` once it matches the expected size and hash (or its
+/// trust-on-first-use record); an existing `dest` is re-checked instead of downloaded again,
+/// unless nothing is known to check it against (an imported installer), since trust on first use
+/// only ever comes from a download.
+/// Every URL tried is recorded in the mirror health. `report` receives throttled progress and
+/// every state change.
+pub fn download(
+    source: &InstallerSource,
+    dest: &Path,
+    cancel: &AtomicBool,
+    policy: RetryPolicy,
+    report: &mut dyn FnMut(&DownloadProgress),
+) -> DownloadProgress {
+    let id = dest
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let expected = &source.expected;
+    let mut transfer = Transfer {
+        progress: DownloadProgress {
+            id: id.clone(),
+            url: source.urls.first().cloned().unwrap_or_default(),
+            state: DownloadState::Downloading,
+            downloaded: 0,
+            total: expected.size,
+            attempt: 0,
+            path: None,
+            sha256: None,
+            verification: None,
+            error: None,
+        },
+        part_source: None,
+        last_report: None,
+        report,
+    };
+
+    if dest.exists() {
+        transfer.set_state(DownloadState::Verifying);
+        match integrity::check(dest, &id, expected) {
+            Ok(verified) if verified.verification != Verification::Unverified => {
+                transfer.progress.downloaded = verified.size;
+                transfer.progress.total = Some(verified.size);
+                return complete(transfer, dest, verified);
+            }
+            // Unknown bytes are kept until a downloaded copy replaces them
+            Ok(_) => {}
+            // A corrupt copy is replaced by a fresh download
+            Err(_) => {
+                let _ = fs::remove_file(dest);
+            }
+        }
+    }
+
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(CONNECT_TIMEOUT)
+        .timeout_read(READ_TIMEOUT)
+        .build();
+    let part = partial_path(dest);
+    let mut errors: Vec<(&String, String)> = Vec::new();
+
+    for (index, url) in source.urls.iter().enumerate() {
+        transfer.progress.url = url.clone();
+        transfer.progress.attempt = 0;
+        // Bytes another URL started may be what failed verification, so give this one a clean try
+        let mut restarted = false;
+        let result = loop {
+            match fetch(&agent, &part, cancel, policy, &mut transfer) {
+                Ok(()) => {}
+                Err(Stop::Cancelled) => {
+                    transfer.set_state(DownloadState::Cancelled);
+                    return transfer.progress;
+                }
+                Err(Stop::Disk(e)) => return fail(transfer, e),
+                Err(Stop::Failed(e)) => break Err(e),
+            }
+            transfer.set_state(DownloadState::Verifying);
+            match integrity::verify(&part, &id, url, expected) {
+                Ok(verified) => break Ok(verified),
+                Err(e) => {
+                    // Never resume from bytes that failed verification
+                    let _ = fs::remove_file(&part);
+                    if transfer.part_source.as_ref() != Some(url) && !restarted {
+                        restarted = true;
+                        continue;
+                    }
+                    break Err(e);
+                }
+            }
+        };
+
+        match result {
+            Ok(verified) => {
+                if let Err(e) = fs::rename(&part, dest) {
+                    return fail(
+                        transfer,
+                        format!("Could not move download to {}: {}", dest.display(), e),
+                    );
+                }
+                let _ = mirrors::record_success(url);
+                return complete(transfer, dest, verified);
+            }
+            Err(e) => {
+                let _ = mirrors::record_failure(url, &e);
+                if index + 1 < source.urls.len() {
+                    transfer.progress.error = Some(format!("{} - trying the next mirror", e));
+                    transfer.set_state(DownloadState::Retrying);
+                    transfer.progress.error = None;
+                }
+                errors.push((url, e));
+            }
+        }
+    }
+
+    let error = match errors.as_slice() {
+        [] => "No download URL".to_string(),
+        [(_, e)] => e.clone(),
+        _ => format!(
+            "All {} mirrors failed - {}",
+            errors.len(),
+            errors
+                .iter()
+                .map(|(url, e)| format!("{}: {}", url, e))
+                .collect::<Vec<_>>()
+                .join("; ")
+        ),
+    };
+    fail(transfer, error)
+}
+
+/// Report a download as failed
+fn fail(mut transfer: Transfer, error: String) -> DownloadProgress {
+    transfer.progress.error = Some(error);
+    transfer.set_state(DownloadState::Failed);
+    transfer.progress
+}
+
+/// Report a verified installer at `dest` as completed
+fn complete(mut transfer: Transfer, dest: &Path, verified: VerifiedInstaller) -> DownloadProgress {
+    transfer.progress.path = Some(dest.to_string_lossy().to_string());
+    transfer.progress.sha256 = Some(verified.sha256);
+    transfer.progress.verification = Some(verified.verification);
+    transfer.progress.error = None;
+    transfer.set_state(DownloadState::Completed);
+    transfer.progress
+}
+
+/// Start downloading a catalog installer (an `ArchiveVersion.download_url`) in the background.
+/// Progress arrives as `download-progress` events; an interrupted download resumes where it stopped.
+#[tauri::command]
+pub fn start_download(app: AppHandle, url: String) -> Result<DownloadProgress, String> {
+    let source = catalog_store::current()
+        .installer_source(&url)
+        .ok_or_else(|| format!("{} is not a catalog download", url))?;
+    // Named after the primary URL whichever mirror is used, so every mirror resumes the same file
+    let id = file_name_from_url(&source.urls[0])?;
+    let dir = get_downloads_dir().ok_or("Could not determine data directory")?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let dest = dir.join(&id);
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let mut running = active().lock().map_err(|e| e.to_string())?;
+        if running.contains_key(&id) {
+            return Err(format!("{} is already downloading", id));
+        }
+        running.insert(id.clone(), cancel.clone());
+    }
+
+    let started = DownloadProgress {
+        id: id.clone(),
+        url: url.clone(),
+        state: if dest.exists() {
+            DownloadState::Verifying
+        } else {
+            DownloadState::Downloading
+        },
+        downloaded: fs::metadata(partial_path(&dest))
+            .map(|m| m.len())
+            .unwrap_or(0),
+        total: source.expected.size,
+        attempt: 1,
+        path: None,
+        sha256: None,
+        verification: None,
+        error: None,
+    };
+
+    std::thread::spawn(move || {
+        let result = download(&source, &dest, &cancel, RetryPolicy::default(), &mut |p| {
+            let _ = app.emit(DOWNLOAD_EVENT, p.clone());
+        });
+        if let Some(sha256) = &result.sha256 {
+            let _ = installers::record_download(&dest, sha256, &result.url);
+        }
+        if let Ok(mut running) = active().lock() {
+            running.remove(&id);
+        }
+    });
+    Ok(started)
+}
+
+/// Stop a running download, keeping the partial file for later
+#[tauri::command]
+pub fn cancel_download(id: String) -> bool {
+    match active().lock().ok().and_then(|r| r.get(&id).cloned()) {
+        Some(cancel) => {
+            cancel.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Completed and partial downloads in the downloads folder
+#[tauri::command]
+pub fn list_downloads() -> Vec<DownloadedFile> {
+    let Some(entries) = get_downloads_dir().and_then(|d| fs::read_dir(d).ok()) else {
+        return Vec::new();
+    };
+    let running: Vec<String> = active()
+        .lock()
+        .map(|r| r.keys().cloned().collect())
+        .unwrap_or_default();
+
+    let mut files: Vec<DownloadedFile> = entries
+        .flatten()
+        .filter(|e| e.path().is_file())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            let (id, complete) = match name.strip_suffix(PARTIAL_SUFFIX) {
+                Some(id) => (id.to_string(), false),
+                None => (name, true),
+            };
+            // Installers only, not library bookkeeping such as in-progress imports
+            if !id.to_ascii_lowercase().ends_with(".exe") {
+                return None;
+            }
+            Some(DownloadedFile {
+                active: running.contains(&id),
+                path: e.path().to_string_lossy().to_string(),
+                size: e.metadata().map(|m| m.len()).unwrap_or(0),
+                id,
+                complete,
+            })
+        })
+        .collect();
+    files.sort_by(|a, b| a.id.cmp(&b.id));
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::catalog::ExpectedInstaller;
+    use crate::commands::test_support::{scratch_local_app_data, Response, TestServer};
+
+    const INSTALLER_LEN: usize = 300_000;
+
+    fn installer() -> Vec<u8> {
+        (0..INSTALLER_LEN).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn source(url: String) -> InstallerSource {
+        InstallerSource {
+            urls: vec![url],
+            expected: ExpectedInstaller::default(),
+        }
+    }
+
+    fn quick_retries(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(50),
+        }
+    }
+
+    /// Serve the installer, honouring Range requests
+    fn serve_ranges(body: Vec<u8>) -> TestServer {
+        TestServer::start(move |req, _| match req.range_start {
+            Some(start) => Response::partial(&body, start as usize),
+            None => Response::ok(&body),
+        })
+    }
+
+    #[test]
+    fn resumes_a_partial_file_from_its_length() {
+        let (_guard, dir) = scratch_local_app_data("download-resume");
+        let body = installer();
+        let dest = dir.join("resume.exe");
+        fs::write(partial_path(&dest), &body[..100_000]).unwrap();
+        let server = serve_ranges(body.clone());
+
+        let progress = download(
+            &source(server.url("/resume.exe")),
+            &dest,
+            &AtomicBool::new(false),
+            quick_retries(3),
+            &mut |_| {},
+        );
+        assert_eq!(progress.state, DownloadState::Completed, "{:?}", progress);
+        assert_eq!(fs::read(&dest).unwrap(), body);
+        assert!(!partial_path(&dest).exists());
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].range_start, Some(100_000));
+    }
+
+    #[test]
+    fn restarts_when_the_server_answers_another_range() {
+        let (_guard, dir) = scratch_local_app_data("download-range-mismatch");
+        let body = installer();
+        let dest = dir.join("mismatch.exe");
+        fs::write(partial_path(&dest), &body[..100_000]).unwrap();
+        let served = body.clone();
+        let server = TestServer::start(move |req, _| match req.range_start {
+            Some(_) => Response::partial(&served, 50_000),
+            None => Response::ok(&served),
+        });
+
+        let mut states = Vec::new();
+        let progress = download(
+            &source(server.url("/mismatch.exe")),
+            &dest,
+            &AtomicBool::new(false),
+            quick_retries(3),
+            &mut |p| states.push(p.state),
+        );
+        assert_eq!(progress.state, DownloadState::Completed, "{:?}", progress);
+        assert_eq!(fs::read(&dest).unwrap(), body);
+        assert!(states.contains(&DownloadState::Retrying));
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].range_start, Some(100_000));
+        assert_eq!(requests[1].range_start, None);
+    }
+
+    #[test]
+    fn a_partial_file_the_server_has_nothing_past_is_complete() {
+        let (_guard, dir) = scratch_local_app_data("download-416");
+        let body = installer();
+        let dest = dir.join("whole.exe");
+        fs::write(partial_path(&dest), &body).unwrap();
+        let server = TestServer::start(|_, _| {
+            Response::status(416).header("Content-Range", format!("bytes */{}", INSTALLER_LEN))
+        });
+
+        let progress = download(
+            &source(server.url("/whole.exe")),
+            &dest,
+            &AtomicBool::new(false),
+            quick_retries(3),
+            &mut |_| {},
+        );
+        assert_eq!(progress.state, DownloadState::Completed, "{:?}", progress);
+        assert_eq!(progress.downloaded, INSTALLER_LEN as u64);
+        assert_eq!(fs::read(&dest).unwrap(), body);
+        assert_eq!(server.requests()[0].range_start, Some(INSTALLER_LEN as u64));
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts_without_data() {
+        let (_guard, dir) = scratch_local_app_data("download-retries");
+        let dest = dir.join("busy.exe");
+        let server = TestServer::start(|_, _| Response::status(503));
+
+        let mut retries = 0;
+        let progress = download(
+            &source(server.url("/busy.exe")),
+            &dest,
+            &AtomicBool::new(false),
+            quick_retries(3),
+            &mut |p| {
+                if p.state == DownloadState::Retrying {
+                    retries += 1;
+                }
+            },
+        );
+        assert_eq!(progress.state, DownloadState::Failed);
+        assert_eq!(progress.attempt, 3);
+        assert_eq!(retries, 2);
+        assert_eq!(server.requests().len(), 3);
+        assert!(progress.error.unwrap().contains("HTTP 503"));
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn fails_over_to_the_next_mirror_and_records_health() {
+        let (_guard, dir) = scratch_local_app_data("download-mirrors");
+        let body = installer();
+        let dest = dir.join("mirrored.exe");
+        let served = body.clone();
+        let server = TestServer::start(move |req, _| match req.path.as_str() {
+            "/primary/mirrored.exe" => Response::status(404),
+            _ => Response::ok(&served),
+        });
+        let primary = server.url("/primary/mirrored.exe");
+        let mirror = server.url("/mirror/mirrored.exe");
+
+        let progress = download(
+            &InstallerSource {
+                urls: vec![primary.clone(), mirror.clone()],
+                expected: ExpectedInstaller::default(),
+            },
+            &dest,
+            &AtomicBool::new(false),
+            quick_retries(3),
+            &mut |_| {},
+        );
+        assert_eq!(progress.state, DownloadState::Completed, "{:?}", progress);
+        assert_eq!(progress.url, mirror);
+        assert_eq!(fs::read(&dest).unwrap(), body);
+        // A 404 is not retried
+        let paths: Vec<String> = server.requests().into_iter().map(|r| r.path).collect();
+        assert_eq!(paths, ["/primary/mirrored.exe", "/mirror/mirrored.exe"]);
+
+        let recorded: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(dir.join("CCVersionGuard").join("mirror_health.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(recorded[&primary]["failures"], 1);
+        assert_eq!(recorded[&primary]["successes"], 0);
+        assert_eq!(recorded[&mirror]["successes"], 1);
+        assert_eq!(recorded[&mirror]["failures"], 0);
+
+        let health = mirrors::get_mirror_health();
+        assert_eq!(health.len(), 2);
+        assert_eq!(health[0].url, primary);
+        assert!(health[0].failing);
+        assert!(health[0]
+            .last_error
+            .as_deref()
+            .unwrap()
+            .contains("HTTP 404"));
+        assert!(!health[1].failing);
+    }
+
+    #[test]
+    fn cancelling_keeps_the_partial_file() {
+        let (_guard, dir) = scratch_local_app_data("download-cancel");
+        let body = installer();
+        let dest = dir.join("cancel.exe");
+        let served = body.clone();
+        let server =
+            TestServer::start(move |_, _| Response::ok(&served).slow(Duration::from_millis(20)));
+
+        let cancel = AtomicBool::new(false);
+        let progress = download(
+            &source(server.url("/cancel.exe")),
+            &dest,
+            &cancel,
+            quick_retries(3),
+            &mut |p| {
+                if p.downloaded > 0 {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+            },
+        );
+        assert_eq!(progress.state, DownloadState::Cancelled);
+        assert!(!dest.exists());
+        let kept = fs::read(partial_path(&dest)).unwrap();
+        assert!(!kept.is_empty() && kept.len() < body.len());
+        assert_eq!(kept, body[..kept.len()]);
+        assert_eq!(progress.downloaded, kept.len() as u64);
+    }
+}
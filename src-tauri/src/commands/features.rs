@@ -0,0 +1,366 @@
+//! Feature knowledge base queries
+//! Feature matrix per release and feature-by-feature comparison of two versions
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::catalog::{Access, Catalog, Feature};
+use super::catalog_store;
+use super::peinfo;
+use super::version::CapCutVersion;
+
+/// Feature state in one release
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureStatus {
+    Free,
+    Paid,
+    Unavailable,
+    /// The knowledge base has no data for this release
+    Unknown,
+}
+
+impl From<Option<Access>> for FeatureStatus {
+    fn from(access: Option<Access>) -> Self {
+        match access {
+            Some(Access::Free) => FeatureStatus::Free,
+            Some(Access::Paid) => FeatureStatus::Paid,
+            Some(Access::Unavailable) => FeatureStatus::Unavailable,
+            None => FeatureStatus::Unknown,
+        }
+    }
+}
+
+/// What happens to a feature when moving between two versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureChange {
+    Unchanged,
+    Gained,
+    Lost,
+    /// Free before, paywalled after
+    Paywalled,
+    /// Paywalled before, free after
+    Unlocked,
+    Unknown,
+}
+
+impl FeatureChange {
+    fn between(from: FeatureStatus, to: FeatureStatus) -> Self {
+        use FeatureStatus::*;
+        match (from, to) {
+            (Unknown, _) | (_, Unknown) => FeatureChange::Unknown,
+            _ if from == to => FeatureChange::Unchanged,
+            (Free, Paid) => FeatureChange::Paywalled,
+            (Paid, Free) => FeatureChange::Unlocked,
+            (Unavailable, _) => FeatureChange::Gained,
+            (_, Unavailable) => FeatureChange::Lost,
+            _ => FeatureChange::Unchanged,
+        }
+    }
+}
+
+/// One feature in a comparison
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureDiff {
+    pub id: String,
+    pub name: String,
+    pub category: String,
+    pub from: FeatureStatus,
+    pub to: FeatureStatus,
+    pub change: FeatureChange,
+}
+
+/// Feature-by-feature comparison of two versions
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionComparison {
+    pub from: String,
+    pub to: String,
+    pub features: Vec<FeatureDiff>,
+    /// Names of features that disappear or become paid
+    pub lost: Vec<String>,
+    /// Names of features that appear or become free
+    pub gained: Vec<String>,
+}
+
+/// Feature statuses of one release
+#[derive(Debug, Clone, Serialize)]
+pub struct ReleaseFeatures {
+    /// "4.0.0"
+    pub release: String,
+    /// Feature id -> status
+    pub features: BTreeMap<String, FeatureStatus>,
+}
+
+/// The knowledge base laid out per catalog release
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureMatrix {
+    pub features: Vec<Feature>,
+    /// Newest first
+    pub releases: Vec<ReleaseFeatures>,
+}
+
+/// Accept a catalog label, version string, installed folder name or version folder path
+fn resolve_version(input: &str) -> Result<(String, CapCutVersion), String> {
+    let path = Path::new(input);
+    if path.is_dir() {
+        let binary = peinfo::read_version_info(&path.join("CapCut.exe"))
+            .ok()
+            .and_then(|info| info.file_version)
+            .and_then(|v| CapCutVersion::parse(&v));
+        let folder = path
+            .file_name()
+            .and_then(|n| CapCutVersion::parse(&n.to_string_lossy()));
+        return binary
+            .or(folder)
+            .map(|v| (v.to_string(), v))
+            .ok_or_else(|| format!("{} does not contain a recognizable version", input));
+    }
+    CapCutVersion::parse(input)
+        .map(|v| (input.to_string(), v))
+        .ok_or_else(|| format!("{} is not a version", input))
+}
+
+/// Compare two versions feature by feature
+#[tauri::command]
+pub fn compare_versions(from: String, to: String) -> Result<VersionComparison, String> {
+    compare(&catalog_store::current(), &from, &to)
+}
+
+fn compare(catalog: &Catalog, from: &str, to: &str) -> Result<VersionComparison, String> {
+    let (from_label, from_version) = resolve_version(from)?;
+    let (to_label, to_version) = resolve_version(to)?;
+
+    let features: Vec<FeatureDiff> = catalog
+        .features
+        .iter()
+        .map(|f| {
+            let before = FeatureStatus::from(f.access(&from_version));
+            let after = FeatureStatus::from(f.access(&to_version));
+            FeatureDiff {
+                change: FeatureChange::between(before, after),
+                id: f.id.clone(),
+                name: f.name.clone(),
+                category: f.category.clone(),
+                from: before,
+                to: after,
+            }
+        })
+        .collect();
+
+    let names = |changes: &[FeatureChange]| {
+        features
+            .iter()
+            .filter(|f| changes.contains(&f.change))
+            .map(|f| f.name.clone())
+            .collect()
+    };
+    Ok(VersionComparison {
+        lost: names(&[FeatureChange::Lost, FeatureChange::Paywalled]),
+        gained: names(&[FeatureChange::Gained, FeatureChange::Unlocked]),
+        from: from_label,
+        to: to_label,
+        features,
+    })
+}
+
+/// Feature status for every release in the catalog
+#[tauri::command]
+pub fn get_feature_matrix() -> FeatureMatrix {
+    feature_matrix(&catalog_store::current())
+}
+
+fn feature_matrix(catalog: &Catalog) -> FeatureMatrix {
+    let mut releases: Vec<CapCutVersion> = catalog
+        .versions
+        .iter()
+        .filter_map(|v| CapCutVersion::parse(&v.label))
+        .map(|v| CapCutVersion::new(v.major, v.minor, v.patch))
+        .collect();
+    releases.sort_by(|a, b| b.cmp(a));
+    releases.dedup();
+
+    FeatureMatrix {
+        releases: releases
+            .iter()
+            .map(|release| ReleaseFeatures {
+                release: release.base(),
+                features: catalog
+                    .features
+                    .iter()
+                    .map(|f| (f.id.clone(), FeatureStatus::from(f.access(release))))
+                    .collect(),
+            })
+            .collect(),
+        features: catalog.features.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::scratch_dir;
+    use serde_json::json;
+    use FeatureStatus::*;
+
+    fn fixture() -> Catalog {
+        let catalog = json!({
+            "schema_version": 1,
+            "catalog_version": 1,
+            "last_compatible": "4.0.0",
+            "personas": [],
+            "features": [
+                {
+                    "id": "auto_captions",
+                    "name": "Auto-captions",
+                    "category": "AI",
+                    "availability": [
+                        { "until": "3.9.0", "access": "free" },
+                        { "from": "4.0.0", "access": "paid" }
+                    ]
+                },
+                {
+                    "id": "text_to_speech",
+                    "name": "Text to speech",
+                    "category": "Audio",
+                    "availability": [
+                        { "until": "3.9.0", "access": "free" },
+                        { "from": "4.0.0", "access": "unavailable" }
+                    ]
+                },
+                {
+                    "id": "ai_avatars",
+                    "name": "AI avatars",
+                    "category": "AI",
+                    "availability": [
+                        { "until": "3.9.0", "access": "unavailable" },
+                        { "from": "4.0.0", "access": "free" }
+                    ]
+                },
+                {
+                    "id": "export_4k",
+                    "name": "4K export",
+                    "category": "Export",
+                    "availability": [{ "access": "free" }]
+                },
+                {
+                    "id": "auto_reframe",
+                    "name": "Auto reframe",
+                    "category": "AI",
+                    "availability": [{ "from": "3.0.0", "until": "3.9.0", "access": "paid" }]
+                }
+            ],
+            "versions": [
+                { "label": "4.0.0", "url": "https://example.com/CapCut_4_0_0_1500_capcutpc_0.exe" },
+                {
+                    "label": "4.0.0 (Beta3)",
+                    "url": "https://example.com/CapCut_4_0_0_1490_beta3_capcutpc_beta.exe"
+                },
+                { "label": "3.2.0", "url": "https://example.com/CapCut_3_2_0_1200_capcutpc_0.exe" }
+            ]
+        });
+        Catalog::parse(&catalog.to_string()).unwrap()
+    }
+
+    fn changes(comparison: &VersionComparison) -> Vec<(&str, FeatureChange)> {
+        comparison
+            .features
+            .iter()
+            .map(|f| (f.id.as_str(), f.change))
+            .collect()
+    }
+
+    #[test]
+    fn changes_between_statuses() {
+        let cases = [
+            (Free, Free, FeatureChange::Unchanged),
+            (Paid, Paid, FeatureChange::Unchanged),
+            (Free, Paid, FeatureChange::Paywalled),
+            (Paid, Free, FeatureChange::Unlocked),
+            (Unavailable, Free, FeatureChange::Gained),
+            (Unavailable, Paid, FeatureChange::Gained),
+            (Free, Unavailable, FeatureChange::Lost),
+            (Paid, Unavailable, FeatureChange::Lost),
+            (Unknown, Free, FeatureChange::Unknown),
+            (Paid, Unknown, FeatureChange::Unknown),
+            (Unknown, Unknown, FeatureChange::Unknown),
+        ];
+        for (from, to, change) in cases {
+            assert_eq!(
+                FeatureChange::between(from, to),
+                change,
+                "{:?} -> {:?}",
+                from,
+                to
+            );
+        }
+    }
+
+    #[test]
+    fn what_moving_from_3_2_to_4_0_loses() {
+        let comparison = compare(&fixture(), "3.2.0", "4.0.0").unwrap();
+        assert_eq!(
+            (comparison.from.as_str(), comparison.to.as_str()),
+            ("3.2.0", "4.0.0")
+        );
+        assert_eq!(comparison.lost, ["Auto-captions", "Text to speech"]);
+        assert_eq!(comparison.gained, ["AI avatars"]);
+        assert_eq!(
+            changes(&comparison),
+            [
+                ("auto_captions", FeatureChange::Paywalled),
+                ("text_to_speech", FeatureChange::Lost),
+                ("ai_avatars", FeatureChange::Gained),
+                ("export_4k", FeatureChange::Unchanged),
+                ("auto_reframe", FeatureChange::Unknown),
+            ]
+        );
+
+        // Going back is the mirror image
+        let back = compare(&fixture(), "4.0.0", "3.2.0").unwrap();
+        assert_eq!(back.lost, ["AI avatars"]);
+        assert_eq!(back.gained, ["Auto-captions", "Text to speech"]);
+    }
+
+    #[test]
+    fn versions_resolve_from_labels_and_folders() {
+        let catalog = fixture();
+        let beta = compare(&catalog, "3.2.0", "4.0.0 (Beta3)").unwrap();
+        assert_eq!(beta.to, "4.0.0 (Beta3)");
+        assert_eq!(beta.lost, ["Auto-captions", "Text to speech"]);
+
+        // An installed folder without a readable CapCut.exe falls back to its name
+        let folder = scratch_dir("features-folder").join("4.0.0.1500");
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(folder.join("CapCut.exe"), b"MZ").unwrap();
+        let installed = compare(&catalog, "3.2.0", &folder.to_string_lossy()).unwrap();
+        assert_eq!(
+            installed.to,
+            CapCutVersion::parse("4.0.0.1500").unwrap().to_string()
+        );
+        assert_eq!(changes(&installed), changes(&beta));
+
+        let empty = scratch_dir("features-empty-folder").join("Apps");
+        std::fs::create_dir_all(&empty).unwrap();
+        assert!(compare(&catalog, "3.2.0", &empty.to_string_lossy()).is_err());
+        assert!(compare(&catalog, "newest", "4.0.0").is_err());
+    }
+
+    #[test]
+    fn matrix_lists_each_release_once_newest_first() {
+        let matrix = feature_matrix(&fixture());
+        let releases: Vec<&str> = matrix.releases.iter().map(|r| r.release.as_str()).collect();
+        assert_eq!(releases, ["4.0.0", "3.2.0"]);
+        assert_eq!(matrix.features.len(), 5);
+
+        let newest = &matrix.releases[0].features;
+        assert_eq!(newest["auto_captions"], Paid);
+        assert_eq!(newest["text_to_speech"], Unavailable);
+        assert_eq!(newest["auto_reframe"], Unknown);
+        let oldest = &matrix.releases[1].features;
+        assert_eq!(oldest["auto_captions"], Free);
+        assert_eq!(oldest["ai_avatars"], Unavailable);
+        assert_eq!(oldest["auto_reframe"], Paid);
+    }
+}
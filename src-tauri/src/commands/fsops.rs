@@ -0,0 +1,574 @@
+//! Filesystem operations that can either run or only be planned
+//! Destructive commands route every change through FsOps so a dry run follows the exact same code path
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use walkdir::WalkDir;
+
+use super::backup::{self, BackupResult};
+
+/// Kind of filesystem change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeAction {
+    Create,
+    Overwrite,
+    MarkReadonly,
+    ClearReadonly,
+    SetAttributes,
+    CreateDir,
+    CopyDir,
+    Move,
+    Delete,
+    Backup,
+}
+
+/// A single intended (or performed) filesystem change
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedChange {
+    pub action: ChangeAction,
+    pub path: String,
+    /// Bytes written, copied or deleted
+    pub bytes: u64,
+    pub detail: Option<String>,
+}
+
+/// Structured list of changes a command would make
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChangePlan {
+    pub success: bool,
+    pub error: Option<String>,
+    pub changes: Vec<PlannedChange>,
+    /// Total bytes removed from disk
+    pub bytes_freed: u64,
+    /// Paths that would be backed up before being changed
+    pub backups: Vec<String>,
+    pub logs: Vec<String>,
+}
+
+/// State of a path after the changes planned so far
+#[derive(Debug, Clone)]
+enum PlannedPath {
+    Absent,
+    File(Vec<u8>),
+    /// Newly created, empty
+    Dir,
+    /// Holds what is on disk at another path (moved or copied there)
+    From(PathBuf),
+}
+
+/// Where to look up a path's planned state
+enum Resolved<'a> {
+    Absent,
+    File(&'a [u8]),
+    Dir,
+    Disk(PathBuf),
+}
+
+/// Executes or plans filesystem changes
+pub struct FsOps {
+    dry_run: bool,
+    changes: Vec<PlannedChange>,
+    bytes_freed: u64,
+    backups: Vec<String>,
+    /// Paths changed by planned steps, so later steps of a plan see them as execution would
+    planned: HashMap<PathBuf, PlannedPath>,
+    /// Read-only flags changed by planned steps
+    planned_readonly: HashMap<PathBuf, bool>,
+}
+
+/// Calculate directory size
+pub fn dir_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Size of a file or directory on disk
+fn path_size(path: &Path) -> u64 {
+    if path.is_dir() {
+        dir_size(path)
+    } else {
+        fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+    }
+}
+
+/// Unset readonly attribute recursively
+fn unset_readonly_recursive(path: &Path) {
+    for entry in WalkDir::new(path).into_iter().filter_map(|e| e.ok()) {
+        let p = entry.path();
+        if let Ok(meta) = fs::metadata(p) {
+            let mut perms = meta.permissions();
+            if perms.readonly() {
+                perms.set_readonly(false);
+                fs::set_permissions(p, perms).ok();
+            }
+        }
+    }
+}
+
+impl FsOps {
+    /// Operations that touch the disk
+    pub fn execute() -> Self {
+        FsOps {
+            dry_run: false,
+            changes: Vec::new(),
+            bytes_freed: 0,
+            backups: Vec::new(),
+            planned: HashMap::new(),
+            planned_readonly: HashMap::new(),
+        }
+    }
+
+    /// Operations that are only recorded
+    pub fn plan() -> Self {
+        FsOps {
+            dry_run: true,
+            ..Self::execute()
+        }
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Planned state of a path: its own entry, or what an entry for one of its parents implies
+    fn resolve(&self, path: &Path) -> Resolved<'_> {
+        if let Some(state) = self.planned.get(path) {
+            return match state {
+                PlannedPath::Absent => Resolved::Absent,
+                PlannedPath::File(contents) => Resolved::File(contents),
+                PlannedPath::Dir => Resolved::Dir,
+                PlannedPath::From(source) => Resolved::Disk(source.clone()),
+            };
+        }
+        for parent in path.ancestors().skip(1) {
+            match self.planned.get(parent) {
+                None => continue,
+                Some(PlannedPath::From(source)) => {
+                    let relative = path.strip_prefix(parent).unwrap_or(path);
+                    return Resolved::Disk(source.join(relative));
+                }
+                // Removed, a file, or a new empty directory: nothing planned lives below it
+                Some(_) => return Resolved::Absent,
+            }
+        }
+        Resolved::Disk(path.to_path_buf())
+    }
+
+    /// Whether a path exists, counting the changes planned so far
+    pub fn exists(&self, path: &Path) -> bool {
+        match self.resolve(path) {
+            Resolved::Absent => false,
+            Resolved::File(_) | Resolved::Dir => true,
+            Resolved::Disk(p) => p.exists(),
+        }
+    }
+
+    /// Whether a path is a directory, counting the changes planned so far
+    pub fn is_dir(&self, path: &Path) -> bool {
+        match self.resolve(path) {
+            Resolved::Dir => true,
+            Resolved::Absent | Resolved::File(_) => false,
+            Resolved::Disk(p) => p.is_dir(),
+        }
+    }
+
+    /// Contents of a file, counting the changes planned so far
+    pub fn read(&self, path: &Path) -> Result<Vec<u8>, String> {
+        match self.resolve(path) {
+            Resolved::File(contents) => Ok(contents.to_vec()),
+            Resolved::Disk(p) => fs::read(p).map_err(|e| e.to_string()),
+            Resolved::Absent | Resolved::Dir => Err(format!("{} is not a file", path.display())),
+        }
+    }
+
+    /// Read-only flag of a path (None if it does not exist), counting the changes planned so far
+    pub fn is_readonly(&self, path: &Path) -> Option<bool> {
+        if !self.exists(path) {
+            return None;
+        }
+        if let Some(readonly) = self.planned_readonly.get(path) {
+            return Some(*readonly);
+        }
+        match self.resolve(path) {
+            Resolved::Disk(p) => fs::metadata(p).ok().map(|m| m.permissions().readonly()),
+            _ => Some(false),
+        }
+    }
+
+    /// Size of a file or directory, counting the changes planned so far
+    fn size(&self, path: &Path) -> u64 {
+        let disk = match self.resolve(path) {
+            Resolved::Absent => return 0,
+            Resolved::File(contents) => return contents.len() as u64,
+            Resolved::Disk(p) if !p.is_dir() => return path_size(&p),
+            Resolved::Disk(p) => Some(p),
+            Resolved::Dir => None,
+        };
+        let changed_below = self
+            .planned
+            .keys()
+            .any(|p| p.starts_with(path) && p.as_path() != path);
+        if let (Some(disk), false) = (&disk, changed_below) {
+            return dir_size(disk);
+        }
+        // Walk the directory as it will be: what is on disk plus what was planned inside it
+        let mut names: Vec<OsString> = disk
+            .iter()
+            .flat_map(|d| fs::read_dir(d).into_iter().flatten())
+            .filter_map(|e| e.ok().map(|e| e.file_name()))
+            .collect();
+        names.extend(
+            self.planned
+                .keys()
+                .filter(|p| p.parent() == Some(path))
+                .filter_map(|p| p.file_name().map(|n| n.to_os_string())),
+        );
+        names.sort();
+        names.dedup();
+        names.iter().map(|name| self.size(&path.join(name))).sum()
+    }
+
+    /// Record a path's planned state, replacing what was planned below it
+    fn plan_path(&mut self, path: &Path, state: PlannedPath) {
+        self.planned.retain(|p, _| !p.starts_with(path));
+        self.planned_readonly.retain(|p, _| !p.starts_with(path));
+        self.planned.insert(path.to_path_buf(), state);
+    }
+
+    fn push(&mut self, action: ChangeAction, path: &Path, bytes: u64, detail: Option<String>) {
+        self.changes.push(PlannedChange {
+            action,
+            path: path.to_string_lossy().to_string(),
+            bytes,
+            detail,
+        });
+    }
+
+    /// Write a file, creating or overwriting it
+    pub fn write(&mut self, path: &Path, contents: impl AsRef<[u8]>) -> Result<(), String> {
+        let contents = contents.as_ref();
+        let action = if self.exists(path) {
+            ChangeAction::Overwrite
+        } else {
+            ChangeAction::Create
+        };
+        self.push(action, path, contents.len() as u64, None);
+        if self.dry_run {
+            // Writing keeps the read-only flag
+            let readonly = self.is_readonly(path).unwrap_or(false);
+            self.plan_path(path, PlannedPath::File(contents.to_vec()));
+            if readonly {
+                self.planned_readonly.insert(path.to_path_buf(), true);
+            }
+            return Ok(());
+        }
+        fs::write(path, contents).map_err(|e| e.to_string())
+    }
+
+    /// Create a directory and its parents
+    pub fn create_dir_all(&mut self, path: &Path) -> Result<(), String> {
+        if self.is_dir(path) {
+            return Ok(());
+        }
+        self.push(ChangeAction::CreateDir, path, 0, None);
+        if self.dry_run {
+            let missing: Vec<PathBuf> = path
+                .ancestors()
+                .take_while(|p| !p.as_os_str().is_empty() && !self.is_dir(p))
+                .map(Path::to_path_buf)
+                .collect();
+            for dir in missing.iter().rev() {
+                self.plan_path(dir, PlannedPath::Dir);
+            }
+            return Ok(());
+        }
+        fs::create_dir_all(path).map_err(|e| e.to_string())
+    }
+
+    /// Delete a file or directory (clearing readonly flags first)
+    pub fn remove(&mut self, path: &Path) -> Result<(), String> {
+        if !self.exists(path) {
+            return Ok(());
+        }
+        let size = self.size(path);
+        self.push(ChangeAction::Delete, path, size, None);
+        self.bytes_freed += size;
+        if self.dry_run {
+            self.plan_path(path, PlannedPath::Absent);
+            return Ok(());
+        }
+        unset_readonly_recursive(path);
+        if path.is_dir() {
+            fs::remove_dir_all(path).map_err(|e| e.to_string())
+        } else {
+            fs::remove_file(path).map_err(|e| e.to_string())
+        }
+    }
+
+    /// Mark a file read-only (always recorded, even if it already is)
+    pub fn mark_readonly(&mut self, path: &Path) -> Result<(), String> {
+        self.push(ChangeAction::MarkReadonly, path, 0, None);
+        if self.dry_run {
+            if !self.exists(path) {
+                return Err(format!("{} does not exist", path.display()));
+            }
+            self.planned_readonly.insert(path.to_path_buf(), true);
+            return Ok(());
+        }
+        let mut perms = fs::metadata(path).map_err(|e| e.to_string())?.permissions();
+        perms.set_readonly(true);
+        fs::set_permissions(path, perms).map_err(|e| e.to_string())
+    }
+
+    /// Set or clear the readonly permission
+    pub fn set_readonly(&mut self, path: &Path, readonly: bool) -> Result<(), String> {
+        let current = self
+            .is_readonly(path)
+            .ok_or_else(|| format!("{} does not exist", path.display()))?;
+        if current == readonly {
+            return Ok(());
+        }
+        let action = if readonly {
+            ChangeAction::MarkReadonly
+        } else {
+            ChangeAction::ClearReadonly
+        };
+        self.push(action, path, 0, None);
+        if self.dry_run {
+            self.planned_readonly.insert(path.to_path_buf(), readonly);
+            return Ok(());
+        }
+        let mut perms = fs::metadata(path).map_err(|e| e.to_string())?.permissions();
+        perms.set_readonly(readonly);
+        fs::set_permissions(path, perms).map_err(|e| e.to_string())
+    }
+
+    /// Apply Windows attribute flags (e.g. "+h", "+s") with `attrib`
+    pub fn set_attributes(&mut self, path: &Path, flags: &[&str]) -> Result<(), String> {
+        if flags.is_empty() {
+            return Ok(());
+        }
+        self.push(ChangeAction::SetAttributes, path, 0, Some(flags.join(" ")));
+        if self.dry_run {
+            return Ok(());
+        }
+        let output = Command::new("attrib")
+            .args(flags)
+            .arg(path)
+            .output()
+            .map_err(|e| e.to_string())?;
+        if !output.status.success() {
+            return Err(format!(
+                "attrib {} failed for {}",
+                flags.join(" "),
+                path.display()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Copy a directory tree
+    pub fn copy_dir(&mut self, src: &Path, dst: &Path) -> Result<(), String> {
+        self.push(
+            ChangeAction::CopyDir,
+            dst,
+            self.size(src),
+            Some(format!("from {}", src.display())),
+        );
+        if self.dry_run {
+            let copy = self.planned_copy(src);
+            self.plan_path(dst, copy);
+            return Ok(());
+        }
+        backup::copy_dir_recursive(src, dst)
+    }
+
+    /// Move (rename) a file or directory on the same volume
+    pub fn rename(&mut self, from: &Path, to: &Path) -> Result<(), String> {
+        self.push(
+            ChangeAction::Move,
+            to,
+            self.size(from),
+            Some(format!("from {}", from.display())),
+        );
+        if self.dry_run {
+            let moved = self.planned_copy(from);
+            let readonly = self.planned_readonly.get(from).copied();
+            // Changes planned below `from` move along with it
+            let below: Vec<(PathBuf, PlannedPath)> = self
+                .planned
+                .iter()
+                .filter(|(p, _)| p.starts_with(from) && p.as_path() != from)
+                .map(|(p, state)| (to.join(p.strip_prefix(from).unwrap_or(p)), state.clone()))
+                .collect();
+            self.plan_path(from, PlannedPath::Absent);
+            self.plan_path(to, moved);
+            self.planned.extend(below);
+            if let Some(readonly) = readonly {
+                self.planned_readonly.insert(to.to_path_buf(), readonly);
+            }
+            return Ok(());
+        }
+        fs::rename(from, to).map_err(|e| e.to_string())
+    }
+
+    /// Planned state of a copy of `source`
+    fn planned_copy(&self, source: &Path) -> PlannedPath {
+        match self.resolve(source) {
+            Resolved::Absent => PlannedPath::Absent,
+            Resolved::File(contents) => PlannedPath::File(contents.to_vec()),
+            Resolved::Dir => PlannedPath::Dir,
+            Resolved::Disk(p) => PlannedPath::From(p),
+        }
+    }
+
+    /// Back up a version directory before it is deleted
+    pub fn backup(&mut self, path: &Path, reason: &str) -> BackupResult {
+        self.push(
+            ChangeAction::Backup,
+            path,
+            dir_size(path),
+            Some(reason.to_string()),
+        );
+        self.backups.push(path.to_string_lossy().to_string());
+        if self.dry_run {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            return BackupResult {
+                success: true,
+                backup_id: Some(format!("{}_planned", name)),
+                error: None,
+            };
+        }
+        backup::create_backup(path, reason)
+    }
+
+    /// Note a snapshot Guard keeps internally (manifest originals, journals)
+    pub fn record_snapshot(&mut self, path: &Path) {
+        self.backups.push(path.to_string_lossy().to_string());
+    }
+
+    /// Finish and return the recorded changes
+    pub fn into_plan(self, result: Result<(), String>, logs: Vec<String>) -> ChangePlan {
+        ChangePlan {
+            success: result.is_ok(),
+            error: result.err(),
+            changes: self.changes,
+            bytes_freed: self.bytes_freed,
+            backups: self.backups,
+            logs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::scratch_dir;
+
+    fn setup(name: &str) -> PathBuf {
+        let root = scratch_dir(name);
+        fs::write(root.join("a.txt"), b"old a").unwrap();
+        fs::write(root.join("c.txt"), b"old c").unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src").join("inner.txt"), b"inner").unwrap();
+        root
+    }
+
+    /// Steps whose recorded actions depend on what earlier steps did
+    fn steps(ops: &mut FsOps, root: &Path) -> Result<(), String> {
+        ops.remove(&root.join("a.txt"))?;
+        ops.write(&root.join("a.txt"), b"new a")?;
+
+        ops.write(&root.join("b.txt"), b"b")?;
+        ops.mark_readonly(&root.join("b.txt"))?;
+        ops.set_readonly(&root.join("b.txt"), true)?;
+        ops.set_readonly(&root.join("b.txt"), false)?;
+
+        ops.create_dir_all(&root.join("new").join("dir"))?;
+        ops.create_dir_all(&root.join("new").join("dir"))?;
+        ops.write(&root.join("new").join("dir").join("x.txt"), b"x")?;
+
+        ops.rename(&root.join("c.txt"), &root.join("d.txt"))?;
+        ops.write(&root.join("d.txt"), b"new d")?;
+        ops.write(&root.join("c.txt"), b"new c")?;
+
+        ops.copy_dir(&root.join("src"), &root.join("copy"))?;
+        ops.write(&root.join("copy").join("inner.txt"), b"changed")?;
+        ops.remove(&root.join("copy"))?;
+        ops.remove(&root.join("copy"))?;
+        ops.remove(&root.join("new"))?;
+        ops.create_dir_all(&root.join("new"))?;
+        ops.write(&root.join("new").join("y.txt"), b"y")
+    }
+
+    fn recorded(plan: &ChangePlan, root: &Path) -> Vec<(ChangeAction, String, u64)> {
+        let root = root.to_string_lossy();
+        plan.changes
+            .iter()
+            .map(|c| (c.action, c.path.replacen(root.as_ref(), "", 1), c.bytes))
+            .collect()
+    }
+
+    #[test]
+    fn plan_records_the_same_steps_as_execution() {
+        let planned_root = setup("fsops-plan");
+        let mut planned = FsOps::plan();
+        let result = steps(&mut planned, &planned_root);
+        let plan = planned.into_plan(result, Vec::new());
+        assert!(plan.success, "{:?}", plan.error);
+        assert_eq!(fs::read(planned_root.join("a.txt")).unwrap(), b"old a");
+        assert!(!planned_root.join("b.txt").exists());
+
+        let executed_root = setup("fsops-execute");
+        let mut executed = FsOps::execute();
+        let result = steps(&mut executed, &executed_root);
+        let run = executed.into_plan(result, Vec::new());
+        assert!(run.success, "{:?}", run.error);
+        assert_eq!(fs::read(executed_root.join("d.txt")).unwrap(), b"new d");
+
+        assert_eq!(
+            recorded(&plan, &planned_root),
+            recorded(&run, &executed_root)
+        );
+        assert_eq!(plan.bytes_freed, run.bytes_freed);
+        let actions: Vec<ChangeAction> = plan.changes.iter().map(|c| c.action).collect();
+        assert_eq!(actions[..2], [ChangeAction::Delete, ChangeAction::Create]);
+        assert!(actions.contains(&ChangeAction::ClearReadonly));
+    }
+
+    #[test]
+    fn plan_reads_see_earlier_planned_steps() {
+        let root = setup("fsops-reads");
+        let mut ops = FsOps::plan();
+        ops.write(&root.join("a.txt"), b"planned").unwrap();
+        ops.rename(&root.join("src"), &root.join("moved")).unwrap();
+        ops.mark_readonly(&root.join("c.txt")).unwrap();
+
+        assert_eq!(ops.read(&root.join("a.txt")).unwrap(), b"planned");
+        assert_eq!(
+            ops.read(&root.join("moved").join("inner.txt")).unwrap(),
+            b"inner"
+        );
+        assert!(!ops.exists(&root.join("src").join("inner.txt")));
+        assert_eq!(ops.is_readonly(&root.join("c.txt")), Some(true));
+        assert_eq!(ops.is_readonly(&root.join("missing.txt")), None);
+        assert!(ops.mark_readonly(&root.join("missing.txt")).is_err());
+        // Nothing touched the disk
+        assert_eq!(fs::read(root.join("a.txt")).unwrap(), b"old a");
+        assert!(root.join("src").is_dir());
+        assert!(!fs::metadata(root.join("c.txt"))
+            .unwrap()
+            .permissions()
+            .readonly());
+    }
+}
@@ -0,0 +1,530 @@
+//! Round-trip INI document model
+//! Edits single keys while keeping comments, ordering, line endings, BOM and encoding intact
+
+use std::fs;
+use std::path::Path;
+
+/// Section CapCut keeps its launcher settings in (configure.ini)
+pub const CONFIGURE_SECTION: &str = "Configure";
+
+/// Text encoding of an INI file on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    /// Legacy single-byte code page, kept byte-for-byte
+    Latin1,
+}
+
+#[derive(Debug, Clone)]
+enum LineKind {
+    /// Blank lines, comments and anything that is not a key or section
+    Other(String),
+    Section {
+        raw: String,
+        name: String,
+    },
+    Entry {
+        /// Everything up to the value (key, `=`, surrounding spaces)
+        prefix: String,
+        key: String,
+        value: String,
+        /// Trailing whitespace after the value
+        suffix: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Line {
+    kind: LineKind,
+    /// Line terminator as found in the file ("" for a final unterminated line)
+    eol: String,
+}
+
+/// A parsed INI file that serializes back to the same bytes unless edited
+#[derive(Debug, Clone)]
+pub struct IniDocument {
+    encoding: Encoding,
+    bom: bool,
+    /// Terminator used for lines added by edits
+    newline: String,
+    lines: Vec<Line>,
+}
+
+impl Default for IniDocument {
+    fn default() -> Self {
+        IniDocument {
+            encoding: Encoding::Utf8,
+            bom: false,
+            newline: "\r\n".to_string(),
+            lines: Vec::new(),
+        }
+    }
+}
+
+/// Whether a BOM-less buffer looks like UTF-16 (ASCII text with interleaved zero bytes)
+fn looks_like_utf16(bytes: &[u8], zero_at_odd: bool) -> bool {
+    if bytes.len() < 2 || !bytes.len().is_multiple_of(2) {
+        return false;
+    }
+    let pairs = bytes.len() / 2;
+    let zeros = bytes
+        .chunks_exact(2)
+        .filter(|c| if zero_at_odd { c[1] == 0 } else { c[0] == 0 })
+        .count();
+    zeros * 2 > pairs
+}
+
+fn decode_utf16(bytes: &[u8], little_endian: bool) -> Result<String, String> {
+    if !bytes.len().is_multiple_of(2) {
+        return Err("Truncated UTF-16 text".to_string());
+    }
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| {
+            if little_endian {
+                u16::from_le_bytes([c[0], c[1]])
+            } else {
+                u16::from_be_bytes([c[0], c[1]])
+            }
+        })
+        .collect();
+    String::from_utf16(&units).map_err(|e| format!("Invalid UTF-16 text: {}", e))
+}
+
+/// Split text into lines, keeping each line's terminator
+fn split_lines(text: &str) -> Vec<(&str, &str)> {
+    let mut lines = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        match rest.find('\n') {
+            Some(i) => {
+                let (line, eol) = if i > 0 && rest.as_bytes()[i - 1] == b'\r' {
+                    (&rest[..i - 1], &rest[i - 1..=i])
+                } else {
+                    (&rest[..i], &rest[i..=i])
+                };
+                lines.push((line, eol));
+                rest = &rest[i + 1..];
+            }
+            None => {
+                lines.push((rest, ""));
+                rest = "";
+            }
+        }
+    }
+    lines
+}
+
+fn parse_line(text: &str) -> LineKind {
+    let trimmed = text.trim();
+    if trimmed.starts_with(';') || trimmed.starts_with('#') {
+        return LineKind::Other(text.to_string());
+    }
+    if trimmed.starts_with('[') && trimmed.ends_with(']') {
+        return LineKind::Section {
+            raw: text.to_string(),
+            name: trimmed[1..trimmed.len() - 1].trim().to_string(),
+        };
+    }
+    let Some(eq) = text.find('=') else {
+        return LineKind::Other(text.to_string());
+    };
+    let key = text[..eq].trim();
+    if key.is_empty() {
+        return LineKind::Other(text.to_string());
+    }
+
+    let after = &text[eq + 1..];
+    let value_start = eq + 1 + (after.len() - after.trim_start().len());
+    let value_end = text.trim_end().len().max(value_start);
+    LineKind::Entry {
+        prefix: text[..value_start].to_string(),
+        key: key.to_string(),
+        value: text[value_start..value_end].to_string(),
+        suffix: text[value_end..].to_string(),
+    }
+}
+
+impl IniDocument {
+    /// An empty document (UTF-8, CRLF line endings)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse raw file bytes, detecting BOM and encoding
+    pub fn parse_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let (encoding, bom, text) = if let Some(rest) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+            let text = String::from_utf8(rest.to_vec()).map_err(|e| e.to_string())?;
+            (Encoding::Utf8, true, text)
+        } else if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+            (Encoding::Utf16Le, true, decode_utf16(rest, true)?)
+        } else if let Some(rest) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+            (Encoding::Utf16Be, true, decode_utf16(rest, false)?)
+        } else if looks_like_utf16(bytes, true) {
+            (Encoding::Utf16Le, false, decode_utf16(bytes, true)?)
+        } else if looks_like_utf16(bytes, false) {
+            (Encoding::Utf16Be, false, decode_utf16(bytes, false)?)
+        } else {
+            match String::from_utf8(bytes.to_vec()) {
+                Ok(text) => (Encoding::Utf8, false, text),
+                Err(_) => (
+                    Encoding::Latin1,
+                    false,
+                    bytes.iter().map(|&b| b as char).collect(),
+                ),
+            }
+        };
+
+        let mut doc = Self::parse(&text);
+        doc.encoding = encoding;
+        doc.bom = bom;
+        Ok(doc)
+    }
+
+    /// Parse already-decoded text
+    pub fn parse(text: &str) -> Self {
+        let lines: Vec<Line> = split_lines(text)
+            .into_iter()
+            .map(|(line, eol)| Line {
+                kind: parse_line(line),
+                eol: eol.to_string(),
+            })
+            .collect();
+        let newline = lines
+            .iter()
+            .map(|l| l.eol.as_str())
+            .find(|eol| !eol.is_empty())
+            .unwrap_or("\r\n")
+            .to_string();
+
+        IniDocument {
+            newline,
+            lines,
+            ..Self::default()
+        }
+    }
+
+    /// Read a file, or start an empty document if it does not exist
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+        let bytes =
+            fs::read(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        Self::parse_bytes(&bytes).map_err(|e| format!("Could not parse {}: {}", path.display(), e))
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Serialize back to text (without BOM)
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for line in &self.lines {
+            match &line.kind {
+                LineKind::Other(raw) | LineKind::Section { raw, .. } => out.push_str(raw),
+                LineKind::Entry {
+                    prefix,
+                    value,
+                    suffix,
+                    ..
+                } => {
+                    out.push_str(prefix);
+                    out.push_str(value);
+                    out.push_str(suffix);
+                }
+            }
+            out.push_str(&line.eol);
+        }
+        out
+    }
+
+    /// Serialize back to bytes in the original encoding
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let text = self.to_text();
+        match self.encoding {
+            Encoding::Utf8 => {
+                let mut out = if self.bom {
+                    vec![0xEF, 0xBB, 0xBF]
+                } else {
+                    Vec::new()
+                };
+                out.extend_from_slice(text.as_bytes());
+                out
+            }
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                let le = self.encoding == Encoding::Utf16Le;
+                let mut out = Vec::with_capacity(text.len() * 2 + 2);
+                if self.bom {
+                    out.extend_from_slice(if le { &[0xFF, 0xFE] } else { &[0xFE, 0xFF] });
+                }
+                for unit in text.encode_utf16() {
+                    out.extend_from_slice(&if le {
+                        unit.to_le_bytes()
+                    } else {
+                        unit.to_be_bytes()
+                    });
+                }
+                out
+            }
+            // Characters outside Latin-1 can only come from edits; replace them
+            Encoding::Latin1 => text
+                .chars()
+                .map(|c| if (c as u32) < 256 { c as u8 } else { b'?' })
+                .collect(),
+        }
+    }
+
+    /// Section names in file order
+    pub fn sections(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|l| match &l.kind {
+                LineKind::Section { name, .. } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Line indices belonging to a section, including its header.
+    /// The empty name addresses keys before the first section header.
+    fn section_range(&self, section: &str) -> Option<(usize, usize)> {
+        let mut start = if section.is_empty() { Some(0) } else { None };
+        for (i, line) in self.lines.iter().enumerate() {
+            if let LineKind::Section { name, .. } = &line.kind {
+                if start.is_some() {
+                    return start.map(|s| (s, i));
+                }
+                if name.eq_ignore_ascii_case(section) {
+                    start = Some(i + 1);
+                }
+            }
+        }
+        start.map(|s| (s, self.lines.len()))
+    }
+
+    fn find_entry(&self, section: &str, key: &str) -> Option<usize> {
+        let (start, end) = self.section_range(section)?;
+        (start..end).find(|&i| {
+            matches!(&self.lines[i].kind, LineKind::Entry { key: k, .. } if k.eq_ignore_ascii_case(key))
+        })
+    }
+
+    /// Read a key (section and key names are case-insensitive, as on Windows)
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        let index = self.find_entry(section, key)?;
+        match &self.lines[index].kind {
+            LineKind::Entry { value, .. } => Some(value.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Set a key, editing it in place if present, otherwise appending it to
+    /// the section (which is created at the end of the file if missing)
+    pub fn set(&mut self, section: &str, key: &str, value: &str) {
+        if let Some(index) = self.find_entry(section, key) {
+            if let LineKind::Entry { value: v, .. } = &mut self.lines[index].kind {
+                *v = value.to_string();
+            }
+            return;
+        }
+
+        let entry = Line {
+            kind: LineKind::Entry {
+                prefix: format!("{}=", key),
+                key: key.to_string(),
+                value: value.to_string(),
+                suffix: String::new(),
+            },
+            eol: self.newline.clone(),
+        };
+
+        match self.section_range(section) {
+            Some((start, end)) => {
+                // Insert after the last non-blank line of the section
+                let mut at = end;
+                while at > start
+                    && matches!(&self.lines[at - 1].kind, LineKind::Other(raw) if raw.trim().is_empty())
+                {
+                    at -= 1;
+                }
+                self.terminate_line(at);
+                self.lines.insert(at, entry);
+            }
+            None => {
+                let len = self.lines.len();
+                self.terminate_line(len);
+                self.lines.push(Line {
+                    kind: LineKind::Section {
+                        raw: format!("[{}]", section),
+                        name: section.to_string(),
+                    },
+                    eol: self.newline.clone(),
+                });
+                self.lines.push(entry);
+            }
+        }
+    }
+
+    /// Make sure the line before `index` ends with a terminator
+    fn terminate_line(&mut self, index: usize) {
+        if index > 0 && self.lines[index - 1].eol.is_empty() {
+            self.lines[index - 1].eol = self.newline.clone();
+        }
+    }
+
+    /// Delete every occurrence of a key in a section. Returns whether anything was removed.
+    pub fn delete(&mut self, section: &str, key: &str) -> bool {
+        let mut removed = false;
+        while let Some(index) = self.find_entry(section, key) {
+            self.lines.remove(index);
+            removed = true;
+        }
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIGURE: &str = "; CapCut launcher settings\r\n\
+                             [Configure]\r\n\
+                             last_version = 5.4.0.1991  \r\n\
+                             ; keep this comment\r\n\
+                             auto_update=1\r\n\
+                             \r\n\
+                             [Other]\r\n\
+                             zeta=1\r\n\
+                             alpha=2";
+
+    fn encode(text: &str, encoding: Encoding, bom: bool) -> Vec<u8> {
+        let mut out = Vec::new();
+        match encoding {
+            Encoding::Utf8 => {
+                if bom {
+                    out.extend_from_slice(&[0xEF, 0xBB, 0xBF]);
+                }
+                out.extend_from_slice(text.as_bytes());
+            }
+            Encoding::Utf16Le => {
+                if bom {
+                    out.extend_from_slice(&[0xFF, 0xFE]);
+                }
+                text.encode_utf16()
+                    .for_each(|u| out.extend_from_slice(&u.to_le_bytes()));
+            }
+            Encoding::Utf16Be => {
+                if bom {
+                    out.extend_from_slice(&[0xFE, 0xFF]);
+                }
+                text.encode_utf16()
+                    .for_each(|u| out.extend_from_slice(&u.to_be_bytes()));
+            }
+            Encoding::Latin1 => out.extend(text.chars().map(|c| c as u8)),
+        }
+        out
+    }
+
+    const ENCODINGS: &[(Encoding, bool)] = &[
+        (Encoding::Utf8, false),
+        (Encoding::Utf8, true),
+        (Encoding::Utf16Le, true),
+        (Encoding::Utf16Le, false),
+        (Encoding::Utf16Be, true),
+        (Encoding::Utf16Be, false),
+    ];
+
+    #[test]
+    fn unedited_document_round_trips_byte_for_byte() {
+        for &(encoding, bom) in ENCODINGS {
+            let bytes = encode(CONFIGURE, encoding, bom);
+            let doc = IniDocument::parse_bytes(&bytes).unwrap();
+            assert_eq!(doc.encoding(), encoding);
+            assert_eq!(doc.to_bytes(), bytes, "{:?} bom={}", encoding, bom);
+        }
+    }
+
+    #[test]
+    fn latin1_round_trips_byte_for_byte() {
+        let bytes = b"; caf\xe9\n[Configure]\nlast_version=1.0\n".to_vec();
+        let mut doc = IniDocument::parse_bytes(&bytes).unwrap();
+        assert_eq!(doc.encoding(), Encoding::Latin1);
+        assert_eq!(doc.to_bytes(), bytes);
+
+        doc.set(CONFIGURE_SECTION, "last_version", "2.0");
+        assert_eq!(
+            doc.to_bytes(),
+            b"; caf\xe9\n[Configure]\nlast_version=2.0\n".to_vec()
+        );
+    }
+
+    #[test]
+    fn set_changes_only_the_touched_line() {
+        let expected = CONFIGURE.replace("5.4.0.1991", "1.0.0.0");
+        for &(encoding, bom) in ENCODINGS {
+            let mut doc = IniDocument::parse_bytes(&encode(CONFIGURE, encoding, bom)).unwrap();
+            doc.set("configure", "LAST_VERSION", "1.0.0.0");
+            assert_eq!(
+                doc.to_bytes(),
+                encode(&expected, encoding, bom),
+                "{:?} bom={}",
+                encoding,
+                bom
+            );
+        }
+    }
+
+    #[test]
+    fn set_appends_to_an_existing_section_in_order() {
+        let mut doc = IniDocument::parse(CONFIGURE);
+        doc.set(CONFIGURE_SECTION, "new_key", "x");
+        assert_eq!(
+            doc.to_text(),
+            CONFIGURE.replace("auto_update=1\r\n", "auto_update=1\r\nnew_key=x\r\n")
+        );
+    }
+
+    #[test]
+    fn set_on_a_missing_section_creates_it_at_the_end() {
+        let mut doc = IniDocument::parse(CONFIGURE);
+        doc.set("Guard", "pinned", "true");
+        assert_eq!(
+            doc.to_text(),
+            format!("{}\r\n[Guard]\r\npinned=true\r\n", CONFIGURE)
+        );
+        assert_eq!(doc.sections(), vec!["Configure", "Other", "Guard"]);
+
+        let mut empty = IniDocument::new();
+        empty.set(CONFIGURE_SECTION, "last_version", "1.0");
+        assert_eq!(empty.to_text(), "[Configure]\r\nlast_version=1.0\r\n");
+    }
+
+    #[test]
+    fn delete_removes_the_last_key_and_keeps_the_section() {
+        let mut doc = IniDocument::parse("[Configure]\nlast_version=1.0\n");
+        assert!(doc.delete(CONFIGURE_SECTION, "last_version"));
+        assert!(!doc.delete(CONFIGURE_SECTION, "last_version"));
+        assert_eq!(doc.get(CONFIGURE_SECTION, "last_version"), None);
+        assert_eq!(doc.to_text(), "[Configure]\n");
+
+        let mut doc = IniDocument::parse(CONFIGURE);
+        assert!(doc.delete("Other", "alpha"));
+        // Only the deleted line goes; the line before keeps its terminator
+        assert_eq!(doc.to_text(), CONFIGURE.replace("alpha=2", ""));
+    }
+
+    #[test]
+    fn get_reads_values_without_padding() {
+        let doc = IniDocument::parse(CONFIGURE);
+        assert_eq!(
+            doc.get(CONFIGURE_SECTION, "last_version"),
+            Some("5.4.0.1991")
+        );
+        assert_eq!(doc.get("other", "Zeta"), Some("1"));
+        assert_eq!(doc.get(CONFIGURE_SECTION, "zeta"), None);
+    }
+}
@@ -0,0 +1,702 @@
+//! Local CapCut installers
+//! Identifies installer files by name and keeps downloaded or imported installers as a local library
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::catalog::{Catalog, ExpectedInstaller};
+use super::catalog_store;
+use super::downloads;
+use super::fsops::{ChangePlan, FsOps};
+use super::integrity;
+use super::paths;
+use super::version::InstallerName;
+
+/// Serializes read-modify-write of the library index
+static LIBRARY_LOCK: Mutex<()> = Mutex::new(());
+
+/// Suffix of an import being copied into the library
+const IMPORT_SUFFIX: &str = ".import";
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// An installer file and the catalog entry it corresponds to
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallerMatch {
+    pub file_name: String,
+    /// "5.4.0.1991 (Beta6)"
+    pub version: String,
+    pub build: Option<u32>,
+    pub channel: String,
+    pub flavor: String,
+    /// Catalog entry with the same build number
+    pub catalog_label: Option<String>,
+}
+
+/// How an installer got into the library
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LibraryOrigin {
+    Downloaded,
+    Imported,
+}
+
+/// Index entry for one library file, so hashes are not recomputed on every listing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LibraryRecord {
+    sha256: String,
+    size: u64,
+    /// File modification time in Unix nanoseconds when hashed; the hash is reused only while
+    /// both it and the size are unchanged
+    #[serde(default)]
+    modified: Option<u64>,
+    /// Unix seconds
+    added_at: u64,
+    origin: LibraryOrigin,
+    /// Download URL or original path
+    #[serde(default)]
+    source: Option<String>,
+}
+
+/// An installer kept in the library
+#[derive(Debug, Clone, Serialize)]
+pub struct LibraryInstaller {
+    pub file_name: String,
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+    /// Unix seconds
+    pub added_at: u64,
+    pub origin: LibraryOrigin,
+    pub source: Option<String>,
+    /// "5.4.0.1991 (Beta6)", if the name is a versioned installer name
+    pub version: Option<String>,
+    pub catalog_label: Option<String>,
+    /// File kept in its place when duplicates are pruned
+    pub duplicate_of: Option<String>,
+}
+
+/// The local installer library
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallerLibrary {
+    pub directory: Option<String>,
+    /// Oldest first
+    pub installers: Vec<LibraryInstaller>,
+    pub total_size: u64,
+    /// Bytes pruning duplicates would free
+    pub duplicate_bytes: u64,
+}
+
+/// Limits for `prune_installers`; duplicates are always removed
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PruneOptions {
+    /// Remove installers added more than this many days ago
+    pub max_age_days: Option<u64>,
+    /// Remove the oldest installers until the library fits
+    pub max_total_bytes: Option<u64>,
+}
+
+/// Result of pruning the library
+#[derive(Debug, Clone, Serialize)]
+pub struct PruneResult {
+    pub success: bool,
+    pub error: Option<String>,
+    pub removed: Vec<String>,
+    pub bytes_freed: u64,
+    pub logs: Vec<String>,
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Installer identity and catalog match for a file name
+fn identify_name(catalog: &Catalog, file_name: &str) -> Result<InstallerMatch, String> {
+    let installer = InstallerName::parse(file_name)
+        .ok_or_else(|| format!("{} is not a versioned CapCut installer name", file_name))?;
+    let entry = catalog.find_build(&installer.version);
+    // The label knows channels the filename omits (test builds)
+    let version = entry
+        .and_then(|e| e.identity())
+        .unwrap_or(installer.version);
+
+    Ok(InstallerMatch {
+        file_name: file_name.to_string(),
+        version: version.to_string(),
+        build: version.build,
+        channel: version.channel.to_string(),
+        flavor: installer.flavor,
+        catalog_label: entry.map(|e| e.label.clone()),
+    })
+}
+
+/// Identify an installer by its filename and match it to the catalog
+pub fn identify(path: &Path) -> Result<InstallerMatch, String> {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or("Invalid installer path")?;
+    identify_name(&catalog_store::current(), &file_name)
+}
+
+/// Identify a local installer file
+#[tauri::command]
+pub fn identify_installer(path: String) -> Result<InstallerMatch, String> {
+    identify(Path::new(&path))
+}
+
+fn get_index_path() -> Option<PathBuf> {
+    paths::get_guard_data_dir().map(|p| p.join("installer_library.json"))
+}
+
+fn load_index() -> BTreeMap<String, LibraryRecord> {
+    get_index_path()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_index(index: &BTreeMap<String, LibraryRecord>) -> Result<(), String> {
+    let path = get_index_path().ok_or("Could not determine data directory")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(index).map_err(|e| e.to_string())?;
+    fs::write(path, content).map_err(|e| e.to_string())
+}
+
+fn modified_nanos(meta: &fs::Metadata) -> Option<u64> {
+    let since_epoch = meta
+        .modified()
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?;
+    u64::try_from(since_epoch.as_nanos()).ok()
+}
+
+fn is_installer_file(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("exe"))
+}
+
+/// Bring the index in line with the library folder: hash new or changed files, drop removed ones.
+/// A dry run leaves the saved index alone.
+fn sync_index(dry_run: bool) -> Result<BTreeMap<String, LibraryRecord>, String> {
+    let dir = downloads::get_downloads_dir().ok_or("Could not determine data directory")?;
+    let _guard = LIBRARY_LOCK.lock().map_err(|e| e.to_string())?;
+    let old = load_index();
+    let mut index = BTreeMap::new();
+
+    for entry in fs::read_dir(&dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if !is_installer_file(&path) {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        let meta = entry.metadata().map_err(|e| e.to_string())?;
+        let modified = modified_nanos(&meta);
+        let record = match old.get(&name) {
+            Some(record)
+                if record.size == meta.len()
+                    && record.modified.is_some()
+                    && record.modified == modified =>
+            {
+                record.clone()
+            }
+            // Dropped in by hand, or replaced or touched since it was indexed
+            previous => {
+                let sha256 = integrity::sha256_file(&path)?;
+                match previous.filter(|r| r.sha256 == sha256) {
+                    Some(record) => LibraryRecord {
+                        size: meta.len(),
+                        modified,
+                        ..record.clone()
+                    },
+                    None => LibraryRecord {
+                        sha256,
+                        size: meta.len(),
+                        modified,
+                        added_at: modified.map_or_else(now, |m| m / 1_000_000_000),
+                        origin: LibraryOrigin::Downloaded,
+                        source: None,
+                    },
+                }
+            }
+        };
+        index.insert(name, record);
+    }
+
+    let changed = index.len() != old.len()
+        || index.iter().any(|(name, r)| {
+            old.get(name)
+                .is_none_or(|o| o.sha256 != r.sha256 || o.modified != r.modified)
+        });
+    if changed && !dry_run {
+        save_index(&index)?;
+    }
+    Ok(index)
+}
+
+/// Record a finished download so its hash is not computed again
+pub fn record_download(path: &Path, sha256: &str, url: &str) -> Result<(), String> {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or("Invalid installer path")?;
+    let meta = fs::metadata(path).map_err(|e| e.to_string())?;
+    let _guard = LIBRARY_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut index = load_index();
+    index.insert(
+        name,
+        LibraryRecord {
+            sha256: sha256.to_string(),
+            size: meta.len(),
+            modified: modified_nanos(&meta),
+            added_at: now(),
+            origin: LibraryOrigin::Downloaded,
+            source: Some(url.to_string()),
+        },
+    );
+    save_index(&index)
+}
+
+/// File names the catalog links to, which win over other copies of the same bytes
+fn catalog_file_names(catalog: &Catalog) -> HashSet<String> {
+    catalog
+        .versions
+        .iter()
+        .map(|v| v.url.as_str())
+        .chain(catalog.personas.iter().map(|p| p.url.as_str()))
+        .filter_map(|url| downloads::file_name_from_url(url).ok())
+        .collect()
+}
+
+/// Library contents, oldest first, with the copy each duplicate defers to
+fn library(catalog: &Catalog, dry_run: bool) -> Result<Vec<LibraryInstaller>, String> {
+    let dir = downloads::get_downloads_dir().ok_or("Could not determine data directory")?;
+    let index = sync_index(dry_run)?;
+    let canonical = catalog_file_names(catalog);
+
+    // Keeper per hash: a catalog-named file, then the oldest, then by name
+    let mut keepers: HashMap<&str, (&String, &LibraryRecord)> = HashMap::new();
+    for (name, record) in &index {
+        let better = |(kept_name, kept): &(&String, &LibraryRecord)| {
+            (!canonical.contains(name.as_str()), record.added_at, name)
+                < (
+                    !canonical.contains(kept_name.as_str()),
+                    kept.added_at,
+                    kept_name,
+                )
+        };
+        match keepers.get(record.sha256.as_str()) {
+            Some(kept) if !better(kept) => {}
+            _ => {
+                keepers.insert(&record.sha256, (name, record));
+            }
+        }
+    }
+
+    let mut installers: Vec<LibraryInstaller> = index
+        .iter()
+        .map(|(name, record)| {
+            let matched = identify_name(catalog, name).ok();
+            let keeper = keepers[record.sha256.as_str()].0;
+            LibraryInstaller {
+                file_name: name.clone(),
+                path: dir.join(name).to_string_lossy().to_string(),
+                size: record.size,
+                sha256: record.sha256.clone(),
+                added_at: record.added_at,
+                origin: record.origin,
+                source: record.source.clone(),
+                version: matched.as_ref().map(|m| m.version.clone()),
+                catalog_label: matched.and_then(|m| m.catalog_label),
+                duplicate_of: (keeper != name).then(|| keeper.clone()),
+            }
+        })
+        .collect();
+    installers.sort_by(|a, b| {
+        a.added_at
+            .cmp(&b.added_at)
+            .then(a.file_name.cmp(&b.file_name))
+    });
+    Ok(installers)
+}
+
+/// List the installers kept locally
+#[tauri::command]
+pub fn list_installer_library() -> Result<InstallerLibrary, String> {
+    let installers = library(&catalog_store::current(), false)?;
+    Ok(InstallerLibrary {
+        directory: downloads::get_downloads_dir().map(|d| d.to_string_lossy().to_string()),
+        total_size: installers.iter().map(|i| i.size).sum(),
+        duplicate_bytes: installers
+            .iter()
+            .filter(|i| i.duplicate_of.is_some())
+            .map(|i| i.size)
+            .sum(),
+        installers,
+    })
+}
+
+/// Copy an installer the user already has into the library.
+/// It is stored under the catalog's file name when it is that exact installer, checked against the
+/// catalog hash or the hash trusted from an earlier download (an import is never trusted itself),
+/// and not copied at all if the library already holds the same bytes.
+#[tauri::command]
+pub fn import_installer(path: String) -> Result<LibraryInstaller, String> {
+    let source = Path::new(&path);
+    if !is_installer_file(source) {
+        return Err(format!("{} is not an installer file", path));
+    }
+    let file_name = source
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or("Invalid installer path")?;
+    let installer = InstallerName::parse(&file_name)
+        .ok_or_else(|| format!("{} is not a versioned CapCut installer name", file_name))?;
+
+    let catalog = catalog_store::current();
+    let entry = catalog
+        .find_build(&installer.version)
+        .filter(|e| e.installer().is_some_and(|i| i.flavor == installer.flavor));
+    let (name, expected) = match entry {
+        Some(e) => (
+            downloads::file_name_from_url(&e.url)?,
+            catalog
+                .installer_source(&e.url)
+                .map(|s| s.expected)
+                .unwrap_or_default(),
+        ),
+        None => (file_name, ExpectedInstaller::default()),
+    };
+    let verified = integrity::check(source, &name, &expected)?;
+
+    let existing = library(&catalog, false)?;
+    if let Some(same) = existing.iter().find(|i| i.sha256 == verified.sha256) {
+        return Ok(same.clone());
+    }
+    if existing.iter().any(|i| i.file_name == name) {
+        return Err(format!(
+            "The library already holds a different {} - remove it first",
+            name
+        ));
+    }
+
+    let dir = downloads::get_downloads_dir().ok_or("Could not determine data directory")?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let dest = dir.join(&name);
+    // Copy under a temporary name so an interrupted import is never listed
+    let temp = dir.join(format!("{}{}", name, IMPORT_SUFFIX));
+    fs::copy(source, &temp).map_err(|e| format!("Could not copy {}: {}", path, e))?;
+    fs::rename(&temp, &dest).map_err(|e| e.to_string())?;
+    let modified = fs::metadata(&dest).ok().and_then(|m| modified_nanos(&m));
+
+    {
+        let _guard = LIBRARY_LOCK.lock().map_err(|e| e.to_string())?;
+        let mut index = load_index();
+        index.insert(
+            name.clone(),
+            LibraryRecord {
+                sha256: verified.sha256,
+                size: verified.size,
+                modified,
+                added_at: now(),
+                origin: LibraryOrigin::Imported,
+                source: Some(path.clone()),
+            },
+        );
+        save_index(&index)?;
+    }
+    library(&catalog, false)?
+        .into_iter()
+        .find(|i| i.file_name == name)
+        .ok_or_else(|| format!("{} was not added to the library", name))
+}
+
+/// The library's installer for a catalog download URL: the same file, or failing that
+/// any installer of the same build, so a deleted version can be reinstalled offline
+#[tauri::command]
+pub fn find_local_installer(url: String) -> Result<Option<LibraryInstaller>, String> {
+    let name = downloads::file_name_from_url(&url)?;
+    let wanted = InstallerName::parse(&url);
+    let installers = library(&catalog_store::current(), false)?;
+
+    let exact = installers.iter().find(|i| i.file_name == name);
+    let same_build = || {
+        let wanted = wanted.as_ref()?;
+        installers.iter().find(|i| {
+            InstallerName::parse(&i.file_name).is_some_and(|i| {
+                i.version.same_release(&wanted.version) && i.version.build == wanted.version.build
+            })
+        })
+    };
+    Ok(exact.or_else(same_build).cloned())
+}
+
+/// Remove duplicates, then installers older than the age limit, then the oldest until under the size limit
+fn prune_with(
+    options: &PruneOptions,
+    ops: &mut FsOps,
+    logs: &mut Vec<String>,
+) -> Result<Vec<String>, String> {
+    let installers = library(&catalog_store::current(), ops.is_dry_run())?;
+    let mut removed: Vec<&LibraryInstaller> = Vec::new();
+
+    for installer in installers.iter().filter(|i| i.duplicate_of.is_some()) {
+        logs.push(format!(
+            "{} is a duplicate of {}",
+            installer.file_name,
+            installer.duplicate_of.as_deref().unwrap_or_default()
+        ));
+        removed.push(installer);
+    }
+
+    let mut kept: Vec<&LibraryInstaller> = installers
+        .iter()
+        .filter(|i| i.duplicate_of.is_none())
+        .collect();
+    if let Some(days) = options.max_age_days {
+        let cutoff = now().saturating_sub(days.saturating_mul(SECONDS_PER_DAY));
+        kept.retain(|i| {
+            if i.added_at < cutoff {
+                logs.push(format!("{} is older than {} days", i.file_name, days));
+                removed.push(i);
+                false
+            } else {
+                true
+            }
+        });
+    }
+    if let Some(limit) = options.max_total_bytes {
+        // `kept` is oldest first
+        let mut total: u64 = kept.iter().map(|i| i.size).sum();
+        let mut oldest = kept.into_iter();
+        while total > limit {
+            let Some(installer) = oldest.next() else {
+                break;
+            };
+            logs.push(format!(
+                "{} removed to fit the {:.1} MB limit",
+                installer.file_name,
+                limit as f64 / (1024.0 * 1024.0)
+            ));
+            total -= installer.size;
+            removed.push(installer);
+        }
+    }
+
+    for installer in &removed {
+        ops.remove(Path::new(&installer.path))
+            .map_err(|e| format!("Failed to delete {}: {}", installer.file_name, e))?;
+    }
+    if !ops.is_dry_run() {
+        sync_index(false)?;
+    }
+    if removed.is_empty() {
+        logs.push("[OK] Nothing to prune".to_string());
+    } else {
+        logs.push(format!("[OK] Pruned {} installer(s)", removed.len()));
+    }
+    Ok(removed.into_iter().map(|i| i.file_name.clone()).collect())
+}
+
+/// Prune the installer library
+#[tauri::command]
+pub fn prune_installers(options: PruneOptions) -> PruneResult {
+    let mut ops = FsOps::execute();
+    let mut logs: Vec<String> = Vec::new();
+    let result = prune_with(&options, &mut ops, &mut logs);
+    let plan = ops.into_plan(Ok(()), Vec::new());
+
+    PruneResult {
+        success: result.is_ok(),
+        bytes_freed: plan.bytes_freed,
+        removed: result.as_ref().cloned().unwrap_or_default(),
+        error: result.err(),
+        logs,
+    }
+}
+
+/// Plan pruning without touching disk
+#[tauri::command]
+pub fn plan_prune_installers(options: PruneOptions) -> ChangePlan {
+    let mut ops = FsOps::plan();
+    let mut logs: Vec<String> = Vec::new();
+    let result = prune_with(&options, &mut ops, &mut logs).map(|_| ());
+    ops.into_plan(result, logs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::catalog::InstallerSource;
+    use crate::commands::downloads::{download, DownloadState, RetryPolicy};
+    use crate::commands::integrity::Verification;
+    use crate::commands::test_support::{scratch_local_app_data, Response, TestServer};
+    use std::sync::atomic::AtomicBool;
+    use std::time::{Duration, SystemTime};
+
+    /// A versioned installer name the catalog does not list
+    const UNLISTED: &str = "CapCut_9_9_0_9999_capcutpc_0.exe";
+
+    fn library_dir() -> PathBuf {
+        let dir = downloads::get_downloads_dir().unwrap();
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn set_modified(path: &Path, seconds: u64) {
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+            .unwrap();
+    }
+
+    #[test]
+    fn hashes_are_reused_only_while_size_and_mtime_match() {
+        let (_guard, _) = scratch_local_app_data("library-mtime");
+        let path = library_dir().join(UNLISTED);
+        fs::write(&path, b"first copy").unwrap();
+        set_modified(&path, 1_000_000);
+        let first = sync_index(false).unwrap()[UNLISTED].sha256.clone();
+        assert_eq!(first, integrity::sha256_file(&path).unwrap());
+
+        // Untouched files keep their cached hash without being read
+        let mut index = load_index();
+        index.get_mut(UNLISTED).unwrap().sha256 = "cached".to_string();
+        save_index(&index).unwrap();
+        assert_eq!(sync_index(false).unwrap()[UNLISTED].sha256, "cached");
+
+        // Same size, new bytes, new mtime
+        fs::write(&path, b"other copy").unwrap();
+        set_modified(&path, 2_000_000);
+        let replaced = &sync_index(false).unwrap()[UNLISTED];
+        assert_eq!(replaced.sha256, integrity::sha256_file(&path).unwrap());
+        assert_ne!(replaced.sha256, first);
+        assert_eq!(load_index()[UNLISTED].sha256, replaced.sha256);
+    }
+
+    #[test]
+    fn imports_are_checked_against_trust_but_never_recorded() {
+        let (_guard, dir) = scratch_local_app_data("library-import-trust");
+        let trust_path = dir.join("CCVersionGuard").join("trusted_installers.json");
+        let outside = dir.join("outside");
+        fs::create_dir_all(&outside).unwrap();
+
+        let unknown = outside.join(UNLISTED);
+        fs::write(&unknown, b"user supplied").unwrap();
+        let imported = import_installer(unknown.to_string_lossy().to_string()).unwrap();
+        assert_eq!(imported.origin, LibraryOrigin::Imported);
+        assert!(!trust_path.exists());
+
+        // A download of the same installer is still trusted from the network, not the import
+        let server = TestServer::start(|_, _| Response::ok(b"served copy"));
+        let dest = library_dir().join(UNLISTED);
+        let source = InstallerSource {
+            urls: vec![server.url(&format!("/{}", UNLISTED))],
+            expected: ExpectedInstaller::default(),
+        };
+        let progress = download(
+            &source,
+            &dest,
+            &AtomicBool::new(false),
+            RetryPolicy::default(),
+            &mut |_| {},
+        );
+        assert_eq!(progress.state, DownloadState::Completed, "{:?}", progress);
+        assert_eq!(progress.verification, Some(Verification::FirstUse));
+        assert_eq!(fs::read(&dest).unwrap(), b"served copy");
+        assert!(trust_path.exists());
+
+        // Once trusted, imports must match the trusted copy
+        let tampered_dir = outside.join("tampered");
+        fs::create_dir_all(&tampered_dir).unwrap();
+        let tampered = tampered_dir.join(UNLISTED);
+        fs::write(&tampered, b"tampered bytes").unwrap();
+        let err = import_installer(tampered.to_string_lossy().to_string()).unwrap_err();
+        assert!(err.contains("first trusted"), "{}", err);
+    }
+
+    /// An installer file dropped into the library, last modified `days_ago`
+    fn drop_in(name: &str, contents: &[u8], days_ago: u64) -> PathBuf {
+        let path = library_dir().join(name);
+        fs::write(&path, contents).unwrap();
+        set_modified(&path, now() - days_ago * SECONDS_PER_DAY);
+        path
+    }
+
+    fn planned_removals(plan: &ChangePlan) -> Vec<String> {
+        plan.changes
+            .iter()
+            .map(|c| {
+                Path::new(&c.path)
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn duplicates_defer_to_the_catalog_name_and_dry_runs_write_nothing() {
+        let (_guard, _) = scratch_local_app_data("library-prune-duplicates");
+        let catalog_name = catalog_file_names(&catalog_store::current())
+            .into_iter()
+            .find(|n| n.ends_with(".exe"))
+            .unwrap();
+        // The other copy is older, but the catalog's name wins
+        let keeper = drop_in(&catalog_name, b"same bytes", 1);
+        let copy = drop_in("installer-copy.exe", b"same bytes", 5);
+
+        let plan = plan_prune_installers(PruneOptions::default());
+        assert!(plan.success, "{:?}", plan.error);
+        assert_eq!(planned_removals(&plan), ["installer-copy.exe"]);
+        assert_eq!(plan.bytes_freed, 10);
+        assert!(copy.exists());
+        assert!(!get_index_path().unwrap().exists());
+
+        let result = prune_installers(PruneOptions::default());
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.removed, ["installer-copy.exe"]);
+        assert!(keeper.exists());
+        assert!(!copy.exists());
+        assert_eq!(load_index().keys().collect::<Vec<_>>(), [&catalog_name]);
+    }
+
+    #[test]
+    fn age_and_size_limits_remove_the_oldest_first() {
+        let (_guard, _) = scratch_local_app_data("library-prune-limits");
+        drop_in("stale.exe", b"stale", 200);
+        drop_in("older.exe", b"older", 10);
+        let newest = drop_in("newest.exe", b"newest", 1);
+        let options = PruneOptions {
+            max_age_days: Some(30),
+            max_total_bytes: Some(6),
+        };
+
+        let plan = plan_prune_installers(options.clone());
+        assert_eq!(planned_removals(&plan), ["stale.exe", "older.exe"]);
+
+        let result = prune_installers(options);
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.removed, ["stale.exe", "older.exe"]);
+        assert_eq!(result.bytes_freed, 10);
+        assert!(newest.exists());
+        let left = list_installer_library().unwrap();
+        assert_eq!(left.installers.len(), 1);
+        assert_eq!(left.total_size, 6);
+    }
+}
@@ -0,0 +1,258 @@
+//! Installer integrity
+//! Checks installers against catalog hashes, trusting the first copy seen when the catalog has none
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::catalog::ExpectedInstaller;
+use super::paths;
+
+/// Serializes read-modify-write of the trust records between concurrent downloads
+static TRUST_LOCK: Mutex<()> = Mutex::new(());
+
+/// What an installer's hash was checked against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verification {
+    /// The hash published in the catalog
+    Catalog,
+    /// No known hash: this copy's hash was recorded as the trusted one
+    FirstUse,
+    /// The hash recorded when the installer was first seen
+    Recorded,
+    /// No known hash and nothing recorded: accepted by a check, but not trusted
+    Unverified,
+}
+
+/// Hash recorded the first time an installer without a catalog hash was downloaded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustRecord {
+    pub sha256: String,
+    pub size: u64,
+    /// Download URL of the first copy
+    pub url: String,
+    /// Unix seconds
+    pub recorded_at: u64,
+}
+
+/// An installer that passed verification
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifiedInstaller {
+    pub sha256: String,
+    pub size: u64,
+    pub verification: Verification,
+}
+
+/// Trust-on-first-use records, keyed by installer file name
+fn get_trust_path() -> Option<PathBuf> {
+    paths::get_guard_data_dir().map(|p| p.join("trusted_installers.json"))
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn load_records() -> BTreeMap<String, TrustRecord> {
+    get_trust_path()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_records(records: &BTreeMap<String, TrustRecord>) -> Result<(), String> {
+    let path = get_trust_path().ok_or("Could not determine data directory")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(records).map_err(|e| e.to_string())?;
+    fs::write(path, content).map_err(|e| e.to_string())
+}
+
+/// Hex-encoded SHA-256 of a file, read in chunks
+pub fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file =
+        File::open(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)
+        .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// Check an installer file against the catalog's size and hash, or against the hash recorded
+/// the first time `id` was seen. Installers seen for the first time are recorded.
+pub fn verify(
+    path: &Path,
+    id: &str,
+    url: &str,
+    expected: &ExpectedInstaller,
+) -> Result<VerifiedInstaller, String> {
+    verify_with(path, id, expected, Some(url))
+}
+
+/// Like `verify`, but an installer seen for the first time is not recorded, so files the user
+/// supplies never become the trusted copy
+pub fn check(
+    path: &Path,
+    id: &str,
+    expected: &ExpectedInstaller,
+) -> Result<VerifiedInstaller, String> {
+    verify_with(path, id, expected, None)
+}
+
+/// Verify, recording a first-seen installer as downloaded from `trust_url` if given
+fn verify_with(
+    path: &Path,
+    id: &str,
+    expected: &ExpectedInstaller,
+    trust_url: Option<&str>,
+) -> Result<VerifiedInstaller, String> {
+    let size = fs::metadata(path)
+        .map_err(|e| format!("Could not read {}: {}", path.display(), e))?
+        .len();
+    if let Some(expected_size) = expected.size.filter(|s| *s != size) {
+        return Err(format!(
+            "{} is {} bytes but the catalog expects {} - the installer is corrupt or was replaced",
+            id, size, expected_size
+        ));
+    }
+
+    let sha256 = sha256_file(path)?;
+    if let Some(expected_hash) = &expected.sha256 {
+        if *expected_hash != sha256 {
+            return Err(format!(
+                "SHA-256 mismatch for {}: the catalog expects {}, this copy is {}",
+                id, expected_hash, sha256
+            ));
+        }
+        return Ok(VerifiedInstaller {
+            sha256,
+            size,
+            verification: Verification::Catalog,
+        });
+    }
+
+    let _guard = TRUST_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut records = load_records();
+    let verification = match records.get(id) {
+        Some(record) if record.sha256 != sha256 => {
+            return Err(format!(
+                "SHA-256 mismatch for {}: the copy first seen was {}, this one is {} - \
+                 the installer has changed since it was first trusted",
+                id, record.sha256, sha256
+            ));
+        }
+        Some(_) => Verification::Recorded,
+        None => match trust_url {
+            Some(url) => {
+                records.insert(
+                    id.to_string(),
+                    TrustRecord {
+                        sha256: sha256.clone(),
+                        size,
+                        url: url.to_string(),
+                        recorded_at: now(),
+                    },
+                );
+                save_records(&records)?;
+                Verification::FirstUse
+            }
+            None => Verification::Unverified,
+        },
+    };
+    Ok(VerifiedInstaller {
+        sha256,
+        size,
+        verification,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::scratch_local_app_data;
+
+    const INSTALLER: &[u8] = b"MZ installer bytes";
+    /// SHA-256 of INSTALLER
+    fn installer_hash() -> String {
+        Sha256::digest(INSTALLER)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    #[test]
+    fn catalog_size_and_hash_are_enforced() {
+        let (_guard, dir) = scratch_local_app_data("integrity-catalog");
+        let path = dir.join("CapCut_5_4_0_1991.exe");
+        fs::write(&path, INSTALLER).unwrap();
+
+        let expected = ExpectedInstaller {
+            size: Some(INSTALLER.len() as u64),
+            sha256: Some(installer_hash()),
+        };
+        let verified = verify(&path, "CapCut_5_4_0_1991.exe", "https://a", &expected).unwrap();
+        assert_eq!(verified.verification, Verification::Catalog);
+        assert_eq!(verified.sha256, installer_hash());
+
+        let wrong_size = ExpectedInstaller {
+            size: Some(INSTALLER.len() as u64 + 1),
+            ..expected.clone()
+        };
+        let err = verify(&path, "CapCut_5_4_0_1991.exe", "https://a", &wrong_size).unwrap_err();
+        assert!(err.contains("bytes but the catalog expects"), "{}", err);
+
+        let wrong_hash = ExpectedInstaller {
+            sha256: Some("0".repeat(64)),
+            ..expected
+        };
+        let err = verify(&path, "CapCut_5_4_0_1991.exe", "https://a", &wrong_hash).unwrap_err();
+        assert!(err.contains("SHA-256 mismatch"), "{}", err);
+        assert!(err.contains(&installer_hash()), "{}", err);
+
+        // Catalog-checked installers are never recorded for trust on first use
+        assert!(load_records().is_empty());
+    }
+
+    #[test]
+    fn unpinned_installers_are_trusted_on_first_use_and_rechecked() {
+        let (_guard, dir) = scratch_local_app_data("integrity-tofu");
+        let path = dir.join("CapCut_3_9_0_1459.exe");
+        fs::write(&path, INSTALLER).unwrap();
+        let unpinned = ExpectedInstaller::default();
+
+        let first = verify(&path, "CapCut_3_9_0_1459.exe", "https://a", &unpinned).unwrap();
+        assert_eq!(first.verification, Verification::FirstUse);
+        let records = load_records();
+        let record = &records["CapCut_3_9_0_1459.exe"];
+        assert_eq!(record.sha256, installer_hash());
+        assert_eq!(record.size, INSTALLER.len() as u64);
+        assert_eq!(record.url, "https://a");
+
+        let again = verify(&path, "CapCut_3_9_0_1459.exe", "https://b", &unpinned).unwrap();
+        assert_eq!(again.verification, Verification::Recorded);
+
+        fs::write(&path, b"MZ tampered bytes").unwrap();
+        let err = verify(&path, "CapCut_3_9_0_1459.exe", "https://b", &unpinned).unwrap_err();
+        assert!(
+            err.contains("has changed since it was first trusted"),
+            "{}",
+            err
+        );
+        // The first copy stays the trusted one
+        assert_eq!(
+            load_records()["CapCut_3_9_0_1459.exe"].sha256,
+            installer_hash()
+        );
+    }
+}
@@ -0,0 +1,415 @@
+//! Protection manifest
+//! Records the original state of every file touched by protection so it can be restored exactly
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::fsops::{self, FsOps};
+use super::strategy::StrategySpec;
+use super::{backup, paths};
+
+const MANIFEST_FILE: &str = "manifest.json";
+const ORIGINALS_DIR: &str = "originals";
+
+/// What existed at a protected path before protection touched it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OriginalState {
+    /// Nothing existed at the path
+    Missing,
+    /// A regular file, stored by content hash
+    File {
+        size: u64,
+        sha256: String,
+        readonly: bool,
+        /// Raw Windows file attributes (None on other platforms)
+        attributes: Option<u32>,
+    },
+    /// A directory, stashed as a full copy
+    Directory { stash: String, size: u64 },
+}
+
+impl OriginalState {
+    /// Name of the blob or stash holding the original under `originals/`
+    fn stored_name(&self) -> Option<&str> {
+        match self {
+            OriginalState::Missing => None,
+            OriginalState::File { sha256, .. } => Some(sha256),
+            OriginalState::Directory { stash, .. } => Some(stash),
+        }
+    }
+}
+
+/// A single file touched by protection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Absolute path of the protected file
+    pub path: String,
+    /// Snapshot of the path before protection
+    pub original: OriginalState,
+    /// When the snapshot was taken (Unix timestamp)
+    pub recorded_at: u64,
+}
+
+/// Persisted record of everything protection has changed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProtectionManifest {
+    pub created_at: u64,
+    pub entries: Vec<ManifestEntry>,
+    /// Strategies applied by the last protection run, with their options
+    #[serde(default)]
+    pub applied: Vec<StrategySpec>,
+    /// When protection was last applied (Unix timestamp)
+    #[serde(default)]
+    pub applied_at: Option<u64>,
+    /// Directory holding manifest.json and the original file contents
+    #[serde(skip)]
+    store: PathBuf,
+}
+
+/// Get the manifest store directory
+pub fn get_manifest_dir() -> Option<PathBuf> {
+    paths::get_guard_data_dir().map(|p| p.join("Protection"))
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Hex-encoded SHA-256 of a byte slice
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(windows)]
+fn read_attributes(meta: &fs::Metadata) -> Option<u32> {
+    use std::os::windows::fs::MetadataExt;
+    Some(meta.file_attributes())
+}
+
+#[cfg(not(windows))]
+fn read_attributes(_meta: &fs::Metadata) -> Option<u32> {
+    None
+}
+
+const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+
+impl ProtectionManifest {
+    /// Open the manifest in a store directory, starting a new one if none exists
+    pub fn open(store: &Path) -> Result<Self, String> {
+        let manifest_path = store.join(MANIFEST_FILE);
+        let mut manifest = if manifest_path.exists() {
+            let content = fs::read_to_string(&manifest_path)
+                .map_err(|e| format!("Could not read protection manifest: {}", e))?;
+            serde_json::from_str::<ProtectionManifest>(&content)
+                .map_err(|e| format!("Invalid protection manifest: {}", e))?
+        } else {
+            ProtectionManifest {
+                created_at: now(),
+                ..Default::default()
+            }
+        };
+        manifest.store = store.to_path_buf();
+        Ok(manifest)
+    }
+
+    /// Open the manifest in the default store directory
+    pub fn open_default() -> Result<Self, String> {
+        let store = get_manifest_dir().ok_or("Could not determine manifest directory")?;
+        Self::open(&store)
+    }
+
+    /// Load an existing manifest, if any protection has been recorded
+    pub fn load(store: &Path) -> Option<Self> {
+        if !store.join(MANIFEST_FILE).exists() {
+            return None;
+        }
+        Self::open(store).ok().filter(|m| !m.is_empty())
+    }
+
+    /// Whether nothing is recorded (no snapshots and no applied strategies)
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.applied.is_empty()
+    }
+
+    /// Remember that a strategy was applied (replacing an earlier spec with the same id)
+    pub fn mark_applied(&mut self, spec: &StrategySpec) {
+        self.applied.retain(|s| s.id != spec.id);
+        self.applied.push(spec.clone());
+        self.applied_at = Some(now());
+    }
+
+    /// Options a strategy was last applied with (Null if never applied)
+    pub fn applied_options(&self, id: &str) -> serde_json::Value {
+        self.applied
+            .iter()
+            .find(|s| s.id == id)
+            .map(|s| s.options.clone())
+            .unwrap_or_default()
+    }
+
+    /// Whether a path has already been snapshotted
+    pub fn contains(&self, path: &Path) -> bool {
+        let key = path.to_string_lossy();
+        self.entries.iter().any(|e| e.path == key)
+    }
+
+    /// Original bytes recorded for a file, if it existed before protection
+    pub fn original_bytes(&self, path: &Path) -> Option<Vec<u8>> {
+        let key = path.to_string_lossy();
+        match &self.entries.iter().find(|e| e.path == key)?.original {
+            OriginalState::File { sha256, .. } => {
+                fs::read(self.store.join(ORIGINALS_DIR).join(sha256)).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Snapshot a path before it is modified.
+    /// Paths already in the manifest keep their first snapshot, so re-applying
+    /// protection never replaces the user's original with a blocker.
+    pub fn record(&mut self, path: &Path, ops: &mut FsOps) -> Result<(), String> {
+        if self.contains(path) {
+            return Ok(());
+        }
+
+        let originals = self.store.join(ORIGINALS_DIR);
+        let dry_run = ops.is_dry_run();
+        if !dry_run {
+            fs::create_dir_all(&originals)
+                .map_err(|e| format!("Failed to create manifest store: {}", e))?;
+        }
+
+        // Read through ops so a dry run records what earlier planned steps left there
+        let original = if !ops.exists(path) {
+            OriginalState::Missing
+        } else if ops.is_dir(path) {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let stash = format!("dir_{}_{}", now(), name);
+            if !dry_run {
+                backup::copy_dir_recursive(path, &originals.join(&stash))
+                    .map_err(|e| format!("Failed to snapshot {}: {}", path.display(), e))?;
+            }
+            ops.record_snapshot(path);
+            OriginalState::Directory {
+                stash,
+                size: fsops::dir_size(path),
+            }
+        } else {
+            let bytes = ops
+                .read(path)
+                .map_err(|e| format!("Failed to snapshot {}: {}", path.display(), e))?;
+            let sha256 = sha256_hex(&bytes);
+            let blob = originals.join(&sha256);
+            if !dry_run && !blob.exists() {
+                fs::write(&blob, &bytes).map_err(|e| format!("Failed to store original: {}", e))?;
+            }
+            ops.record_snapshot(path);
+            OriginalState::File {
+                size: bytes.len() as u64,
+                sha256,
+                readonly: ops.is_readonly(path).unwrap_or(false),
+                attributes: fs::metadata(path).ok().and_then(|m| read_attributes(&m)),
+            }
+        };
+
+        self.entries.push(ManifestEntry {
+            path: path.to_string_lossy().to_string(),
+            original,
+            recorded_at: now(),
+        });
+        Ok(())
+    }
+
+    /// Forget every snapshot after the first `len`, deleting stored originals
+    /// that no remaining entry refers to
+    pub fn truncate(&mut self, len: usize) {
+        let dropped = self.entries.split_off(len.min(self.entries.len()));
+        let originals = self.store.join(ORIGINALS_DIR);
+        for entry in &dropped {
+            let Some(name) = entry.original.stored_name() else {
+                continue;
+            };
+            if self
+                .entries
+                .iter()
+                .any(|e| e.original.stored_name() == Some(name))
+            {
+                continue;
+            }
+            let stored = originals.join(name);
+            if stored.is_dir() {
+                fs::remove_dir_all(&stored).ok();
+            } else {
+                fs::remove_file(&stored).ok();
+            }
+        }
+    }
+
+    /// Persist the manifest to its store directory
+    pub fn save(&self) -> Result<(), String> {
+        fs::create_dir_all(&self.store)
+            .map_err(|e| format!("Failed to create manifest store: {}", e))?;
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(self.store.join(MANIFEST_FILE), content)
+            .map_err(|e| format!("Failed to save protection manifest: {}", e))
+    }
+
+    /// Restore a single entry to its recorded original state
+    fn restore_entry(&self, entry: &ManifestEntry, ops: &mut FsOps) -> Result<(), String> {
+        let path = PathBuf::from(&entry.path);
+        ops.remove(&path)?;
+
+        match &entry.original {
+            OriginalState::Missing => Ok(()),
+            OriginalState::Directory { stash, .. } => {
+                ops.copy_dir(&self.store.join(ORIGINALS_DIR).join(stash), &path)
+            }
+            OriginalState::File {
+                sha256,
+                readonly,
+                attributes,
+                ..
+            } => {
+                let bytes = fs::read(self.store.join(ORIGINALS_DIR).join(sha256))
+                    .map_err(|e| format!("Original content missing: {}", e))?;
+                if &sha256_hex(&bytes) != sha256 {
+                    return Err("Stored original is corrupted (hash mismatch)".to_string());
+                }
+                if let Some(parent) = path.parent() {
+                    ops.create_dir_all(parent)?;
+                }
+                ops.write(&path, &bytes)?;
+
+                if let Some(attrs) = attributes {
+                    let mut flags = Vec::new();
+                    if attrs & FILE_ATTRIBUTE_HIDDEN != 0 {
+                        flags.push("+h");
+                    }
+                    if attrs & FILE_ATTRIBUTE_SYSTEM != 0 {
+                        flags.push("+s");
+                    }
+                    ops.set_attributes(&path, &flags).ok();
+                }
+                if *readonly {
+                    ops.mark_readonly(&path)
+                        .map_err(|e| format!("Could not restore read-only flag: {}", e))?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Restore every recorded path, newest first.
+    /// Returns Err if any entry could not be restored; the manifest is kept in that case.
+    pub fn restore(&self, ops: &mut FsOps, logs: &mut Vec<String>) -> Result<(), String> {
+        self.restore_where(|_| true, ops, logs)
+    }
+
+    /// Restore only the recorded paths matching a filter, newest first
+    pub fn restore_where<F>(
+        &self,
+        filter: F,
+        ops: &mut FsOps,
+        logs: &mut Vec<String>,
+    ) -> Result<(), String>
+    where
+        F: Fn(&ManifestEntry) -> bool,
+    {
+        let mut failures = 0;
+
+        for entry in self.entries.iter().rev().filter(|e| filter(e)) {
+            let name = Path::new(&entry.path)
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            match self.restore_entry(entry, ops) {
+                Ok(()) => match entry.original {
+                    OriginalState::Missing => logs.push(format!("[OK] Removed {}", name)),
+                    _ => logs.push(format!("[OK] Restored original {}", name)),
+                },
+                Err(e) => {
+                    failures += 1;
+                    logs.push(format!("[!] Could not restore {}: {}", name, e));
+                }
+            }
+        }
+
+        if failures > 0 {
+            Err(format!("{} file(s) could not be restored", failures))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Delete the manifest and all stored originals
+    pub fn clear(&self) -> Result<(), String> {
+        if self.store.exists() {
+            fs::remove_dir_all(&self.store).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::scratch_dir;
+
+    #[test]
+    fn truncate_deletes_unreferenced_originals() {
+        let dir = scratch_dir("manifest-truncate");
+        let store = dir.join("Protection");
+        let kept = dir.join("kept.ini");
+        let shared = dir.join("shared.ini");
+        let dropped = dir.join("dropped.ini");
+        fs::write(&kept, "kept").unwrap();
+        fs::write(&shared, "kept").unwrap();
+        fs::write(&dropped, "dropped").unwrap();
+
+        let mut manifest = ProtectionManifest::open(&store).unwrap();
+        let mut ops = FsOps::execute();
+        manifest.record(&kept, &mut ops).unwrap();
+        manifest.record(&shared, &mut ops).unwrap();
+        manifest.record(&dropped, &mut ops).unwrap();
+        manifest.record(&dir.join("missing.ini"), &mut ops).unwrap();
+
+        manifest.truncate(1);
+
+        assert_eq!(manifest.entries.len(), 1);
+        let originals = store.join(ORIGINALS_DIR);
+        assert!(originals.join(sha256_hex(b"kept")).exists());
+        assert!(!originals.join(sha256_hex(b"dropped")).exists());
+    }
+
+    #[test]
+    fn restore_brings_back_readonly_flag() {
+        let dir = scratch_dir("manifest-readonly");
+        let file = dir.join("configure.ini");
+        fs::write(&file, "original").unwrap();
+        let mut perms = fs::metadata(&file).unwrap().permissions();
+        perms.set_readonly(true);
+        fs::set_permissions(&file, perms).unwrap();
+
+        let mut manifest = ProtectionManifest::open(&dir.join("Protection")).unwrap();
+        let mut ops = FsOps::execute();
+        manifest.record(&file, &mut ops).unwrap();
+        ops.remove(&file).unwrap();
+        ops.write(&file, "blocked").unwrap();
+
+        manifest.restore(&mut ops, &mut Vec::new()).unwrap();
+
+        assert_eq!(fs::read_to_string(&file).unwrap(), "original");
+        assert!(fs::metadata(&file).unwrap().permissions().readonly());
+    }
+}
@@ -0,0 +1,146 @@
+//! Download mirror health
+//! Records which installer URLs succeed or fail so broken catalog entries can be corrected
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use super::catalog::Catalog;
+use super::catalog_store;
+use super::paths;
+
+/// Serializes read-modify-write of the health file between concurrent downloads
+static HEALTH_LOCK: Mutex<()> = Mutex::new(());
+
+/// Outcomes recorded for one URL
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct MirrorRecord {
+    successes: u32,
+    failures: u32,
+    /// Failures since the last success
+    consecutive_failures: u32,
+    /// Unix seconds
+    last_success_at: Option<u64>,
+    last_failure_at: Option<u64>,
+    last_error: Option<String>,
+}
+
+/// Health of one download URL
+#[derive(Debug, Clone, Serialize)]
+pub struct MirrorHealth {
+    pub url: String,
+    /// Catalog version label or persona name the URL belongs to
+    pub catalog_entry: Option<String>,
+    /// The last attempt failed
+    pub failing: bool,
+    pub successes: u32,
+    pub failures: u32,
+    pub consecutive_failures: u32,
+    pub last_success_at: Option<u64>,
+    pub last_failure_at: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+fn get_health_path() -> Option<PathBuf> {
+    paths::get_guard_data_dir().map(|p| p.join("mirror_health.json"))
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn load_records() -> BTreeMap<String, MirrorRecord> {
+    get_health_path()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn update(url: &str, apply: impl FnOnce(&mut MirrorRecord)) -> Result<(), String> {
+    let path = get_health_path().ok_or("Could not determine data directory")?;
+    let _guard = HEALTH_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut records = load_records();
+    apply(records.entry(url.to_string()).or_default());
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(&records).map_err(|e| e.to_string())?;
+    fs::write(path, content).map_err(|e| e.to_string())
+}
+
+/// Record that an installer was downloaded and verified from `url`
+pub fn record_success(url: &str) -> Result<(), String> {
+    update(url, |r| {
+        r.successes += 1;
+        r.consecutive_failures = 0;
+        r.last_success_at = Some(now());
+    })
+}
+
+/// Record that `url` could not deliver a valid installer
+pub fn record_failure(url: &str, error: &str) -> Result<(), String> {
+    update(url, |r| {
+        r.failures += 1;
+        r.consecutive_failures += 1;
+        r.last_failure_at = Some(now());
+        r.last_error = Some(error.to_string());
+    })
+}
+
+/// Catalog version label or persona name that lists `url`
+fn catalog_entry(catalog: &Catalog, url: &str) -> Option<String> {
+    let listed =
+        |primary: &String, mirrors: &[String]| primary == url || mirrors.iter().any(|m| m == url);
+    catalog
+        .versions
+        .iter()
+        .find(|v| listed(&v.url, &v.mirrors))
+        .map(|v| v.label.clone())
+        .or_else(|| {
+            catalog
+                .personas
+                .iter()
+                .find(|p| listed(&p.url, &p.mirrors))
+                .map(|p| p.name.clone())
+        })
+}
+
+/// Recorded health of every download URL, failing ones first
+#[tauri::command]
+pub fn get_mirror_health() -> Vec<MirrorHealth> {
+    let catalog = catalog_store::current();
+    let mut health: Vec<MirrorHealth> = load_records()
+        .into_iter()
+        .map(|(url, r)| MirrorHealth {
+            catalog_entry: catalog_entry(&catalog, &url),
+            failing: r.consecutive_failures > 0,
+            url,
+            successes: r.successes,
+            failures: r.failures,
+            consecutive_failures: r.consecutive_failures,
+            last_success_at: r.last_success_at,
+            last_failure_at: r.last_failure_at,
+            last_error: r.last_error,
+        })
+        .collect();
+    health.sort_by(|a, b| {
+        b.consecutive_failures
+            .cmp(&a.consecutive_failures)
+            .then(a.url.cmp(&b.url))
+    });
+    health
+}
+
+/// Forget recorded mirror health
+#[tauri::command]
+pub fn clear_mirror_health() -> Result<(), String> {
+    match get_health_path().filter(|p| p.exists()) {
+        Some(path) => fs::remove_file(path).map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
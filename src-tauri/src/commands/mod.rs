@@ -1,8 +1,30 @@
 pub mod autostart;
 pub mod backup;
+pub mod catalog;
+pub mod catalog_query;
+pub mod catalog_store;
 pub mod cleaner;
+pub mod downloads;
+pub mod features;
+pub mod fsops;
+pub mod ini;
+pub mod installers;
+pub mod integrity;
+pub mod manifest;
+pub mod mirrors;
+pub mod parking;
 pub mod paths;
+pub mod peinfo;
 pub mod process;
+pub mod productinfo;
 pub mod protector;
+pub mod recommend;
+pub mod risk;
 pub mod scanner;
+pub mod strategy;
 pub mod switcher;
+#[cfg(test)]
+pub mod test_support;
+pub mod transaction;
+pub mod version;
+pub mod watcher;
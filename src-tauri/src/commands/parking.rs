@@ -0,0 +1,315 @@
+//! Version parking
+//! Hides inactive version folders from the CapCut launcher by moving them out of Apps, without a backup copy
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::fsops::{ChangePlan, FsOps};
+use super::manifest::ProtectionManifest;
+use super::{paths, process, switcher};
+
+/// Suffix older releases used when renaming versions in place
+pub const LEGACY_SUFFIX: &str = "_backup";
+
+/// Folder next to Apps that holds parked versions (same volume, so moves are instant)
+const PARKING_DIR: &str = "Parked";
+
+/// A version moved into the parking area
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParkedVersion {
+    pub name: String,
+    /// Where the folder lived in Apps
+    pub original_path: String,
+    /// Where the folder is now
+    pub parked_path: String,
+    /// When it was parked (Unix timestamp)
+    pub parked_at: u64,
+}
+
+/// Result of a park or unpark operation
+#[derive(Debug, Clone, Serialize)]
+pub struct ParkResult {
+    pub success: bool,
+    pub error: Option<String>,
+    pub logs: Vec<String>,
+    /// New location of the version folder
+    pub path: Option<String>,
+}
+
+fn get_registry_path() -> Option<PathBuf> {
+    paths::get_guard_data_dir().map(|p| p.join("parking.json"))
+}
+
+/// Directory parked versions are moved into
+pub fn get_parking_dir() -> Option<PathBuf> {
+    paths::resolve_capcut_paths().map(|p| p.root.join(PARKING_DIR))
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Parked versions whose folders still exist
+pub fn list_parked() -> Vec<ParkedVersion> {
+    load_registry()
+        .into_iter()
+        .filter(|p| Path::new(&p.parked_path).is_dir())
+        .collect()
+}
+
+fn load_registry() -> Vec<ParkedVersion> {
+    get_registry_path()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_registry(parked: &[ParkedVersion]) -> Result<(), String> {
+    let path = get_registry_path().ok_or("Could not determine data directory")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(parked).map_err(|e| e.to_string())?;
+    fs::write(path, content).map_err(|e| format!("Could not save parking registry: {}", e))
+}
+
+fn finish(result: Result<PathBuf, String>, logs: Vec<String>) -> ParkResult {
+    ParkResult {
+        success: result.is_ok(),
+        path: result
+            .as_ref()
+            .ok()
+            .map(|p| p.to_string_lossy().to_string()),
+        error: result.err(),
+        logs,
+    }
+}
+
+/// Move an installed version out of Apps so the launcher no longer sees it
+#[tauri::command]
+pub fn park_version(version_path: String) -> ParkResult {
+    let mut logs = Vec::new();
+    let result = park_version_with(Path::new(&version_path), &mut FsOps::execute(), &mut logs);
+    finish(result, logs)
+}
+
+/// Plan parking a version without touching disk
+#[tauri::command]
+pub fn plan_park_version(version_path: String) -> ChangePlan {
+    let mut ops = FsOps::plan();
+    let mut logs = Vec::new();
+    let result = park_version_with(Path::new(&version_path), &mut ops, &mut logs);
+    ops.into_plan(result.map(|_| ()), logs)
+}
+
+fn park_version_with(
+    version_dir: &Path,
+    ops: &mut FsOps,
+    logs: &mut Vec<String>,
+) -> Result<PathBuf, String> {
+    let apps_path = paths::get_capcut_apps_path().ok_or("CapCut installation not found")?;
+    let parking_dir = get_parking_dir().ok_or("CapCut installation not found")?;
+    let name = version_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or("Invalid version path")?;
+
+    if !version_dir.is_dir() || version_dir.parent() != Some(apps_path.as_path()) {
+        return Err(format!("{} is not an installed version", name));
+    }
+    if name.ends_with(LEGACY_SUFFIX) {
+        return Err(format!("{} is already hidden from the launcher", name));
+    }
+    if process::is_capcut_running() {
+        return Err("Close CapCut before parking a version".to_string());
+    }
+    let manifest = ProtectionManifest::open_default().unwrap_or_default();
+    if switcher::launcher_version(&apps_path, &manifest).as_deref() == Some(name.as_str()) {
+        return Err(format!(
+            "{} is the active version - switch to another version first",
+            name
+        ));
+    }
+
+    let parked_path = parking_dir.join(&name);
+    if parked_path.exists() {
+        return Err(format!("A parked copy of {} already exists", name));
+    }
+
+    logs.push(format!("Parking {}...", name));
+    ops.create_dir_all(&parking_dir)?;
+    ops.rename(version_dir, &parked_path)
+        .map_err(|e| format!("Could not move {}: {}", name, e))?;
+
+    if !ops.is_dry_run() {
+        let mut registry = load_registry();
+        registry.retain(|p| p.name != name);
+        registry.push(ParkedVersion {
+            name: name.clone(),
+            original_path: version_dir.to_string_lossy().to_string(),
+            parked_path: parked_path.to_string_lossy().to_string(),
+            parked_at: now(),
+        });
+        if let Err(e) = save_registry(&registry) {
+            // Without a record the version could not be found again - undo the move
+            ops.rename(&parked_path, version_dir).ok();
+            return Err(e);
+        }
+    }
+
+    logs.push(format!(
+        "[OK] {} parked - hidden from the CapCut launcher",
+        name
+    ));
+    Ok(parked_path)
+}
+
+/// Bring a parked (or legacy "_backup"-renamed) version back into Apps
+#[tauri::command]
+pub fn unpark_version(name: String) -> ParkResult {
+    let mut logs = Vec::new();
+    let result = unpark_version_with(&name, &mut FsOps::execute(), &mut logs);
+    finish(result, logs)
+}
+
+/// Plan unparking a version without touching disk
+#[tauri::command]
+pub fn plan_unpark_version(name: String) -> ChangePlan {
+    let mut ops = FsOps::plan();
+    let mut logs = Vec::new();
+    let result = unpark_version_with(&name, &mut ops, &mut logs);
+    ops.into_plan(result.map(|_| ()), logs)
+}
+
+fn unpark_version_with(
+    name: &str,
+    ops: &mut FsOps,
+    logs: &mut Vec<String>,
+) -> Result<PathBuf, String> {
+    let apps_path = paths::get_capcut_apps_path().ok_or("CapCut installation not found")?;
+    let name = name.strip_suffix(LEGACY_SUFFIX).unwrap_or(name);
+    let mut registry = load_registry();
+
+    let (from, to) = match registry.iter().find(|p| p.name == name) {
+        Some(parked) => (
+            PathBuf::from(&parked.parked_path),
+            PathBuf::from(&parked.original_path),
+        ),
+        None => (
+            apps_path.join(format!("{}{}", name, LEGACY_SUFFIX)),
+            apps_path.join(name),
+        ),
+    };
+
+    if !from.is_dir() {
+        return Err(format!("{} is not parked", name));
+    }
+    if to.exists() {
+        return Err(format!("{} already exists in Apps", name));
+    }
+
+    logs.push(format!("Restoring {} to Apps...", name));
+    ops.rename(&from, &to)
+        .map_err(|e| format!("Could not move {}: {}", name, e))?;
+
+    if !ops.is_dry_run() {
+        registry.retain(|p| p.name != name);
+        if let Err(e) = save_registry(&registry) {
+            logs.push(format!("[!] {}", e));
+        }
+    }
+
+    logs.push(format!("[OK] {} is visible to the launcher again", name));
+    Ok(to)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::scanner::{collect_versions, VersionInfo, VersionState};
+    use crate::commands::test_support::{scratch_install, scratch_local_app_data};
+
+    fn state_of<'a>(versions: &'a [VersionInfo], name: &str) -> Option<(&'a str, VersionState)> {
+        versions
+            .iter()
+            .find(|v| v.name == name)
+            .map(|v| (v.path.as_str(), v.state))
+    }
+
+    #[test]
+    fn park_and_unpark_round_trip() {
+        let (_guard, dir) = scratch_local_app_data("parking-round-trip");
+        let apps = scratch_install(&dir, &["5.0.0.100", "5.1.0.200"], "5.0.0.100");
+        let version = apps.join("5.1.0.200");
+
+        let plan = plan_park_version(version.to_string_lossy().to_string());
+        assert!(plan.success, "{:?}", plan.error);
+        assert!(version.is_dir());
+
+        let parked = park_version(version.to_string_lossy().to_string());
+        assert!(parked.success, "{:?}", parked.error);
+        let parked_path = get_parking_dir().unwrap().join("5.1.0.200");
+        assert_eq!(parked.path, Some(parked_path.to_string_lossy().to_string()));
+        assert!(!version.exists());
+        assert!(parked_path.join("CapCut.exe").is_file());
+        assert_eq!(list_parked().len(), 1);
+
+        let versions = collect_versions();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(
+            state_of(&versions, "5.1.0.200"),
+            Some((parked_path.to_string_lossy().as_ref(), VersionState::Parked))
+        );
+        assert_eq!(
+            state_of(&versions, "5.0.0.100").map(|s| s.1),
+            Some(VersionState::Installed)
+        );
+
+        let restored = unpark_version("5.1.0.200".to_string());
+        assert!(restored.success, "{:?}", restored.error);
+        assert!(version.join("CapCut.exe").is_file());
+        assert!(!parked_path.exists());
+        assert!(list_parked().is_empty());
+        assert_eq!(
+            state_of(&collect_versions(), "5.1.0.200").map(|s| s.1),
+            Some(VersionState::Installed)
+        );
+    }
+
+    #[test]
+    fn active_version_cannot_be_parked() {
+        let (_guard, dir) = scratch_local_app_data("parking-active");
+        let apps = scratch_install(&dir, &["5.0.0.100", "5.1.0.200"], "5.0.0.100");
+
+        let result = park_version(apps.join("5.0.0.100").to_string_lossy().to_string());
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("active version"));
+        assert!(apps.join("5.0.0.100").is_dir());
+    }
+
+    #[test]
+    fn legacy_backup_folders_show_as_parked_and_unpark_in_place() {
+        let (_guard, dir) = scratch_local_app_data("parking-legacy");
+        let apps = scratch_install(&dir, &["5.0.0.100", "5.1.0.200"], "5.0.0.100");
+        let legacy = apps.join(format!("5.1.0.200{}", LEGACY_SUFFIX));
+        fs::rename(apps.join("5.1.0.200"), &legacy).unwrap();
+
+        let versions = collect_versions();
+        assert_eq!(
+            state_of(&versions, "5.1.0.200"),
+            Some((legacy.to_string_lossy().as_ref(), VersionState::Parked))
+        );
+        let result = park_version(legacy.to_string_lossy().to_string());
+        assert!(result.error.unwrap().contains("already hidden"));
+
+        let restored = unpark_version(format!("5.1.0.200{}", LEGACY_SUFFIX));
+        assert!(restored.success, "{:?}", restored.error);
+        assert!(!legacy.exists());
+        assert!(apps.join("5.1.0.200").join("CapCut.exe").is_file());
+        assert!(!unpark_version("5.1.0.200".to_string()).success);
+    }
+}
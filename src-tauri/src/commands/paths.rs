@@ -129,6 +129,13 @@ pub fn get_capcut_root_path() -> Option<PathBuf> {
     resolve_capcut_paths().map(|p| p.root)
 }
 
+/// Get the CC Version Guard data directory (backups, manifests, settings)
+pub fn get_guard_data_dir() -> Option<PathBuf> {
+    std::env::var("LOCALAPPDATA")
+        .ok()
+        .map(|p| PathBuf::from(p).join("CCVersionGuard"))
+}
+
 /// Set a custom CapCut path (for user-specified installations)
 /// Returns the CapCutPaths if valid, None otherwise
 pub fn validate_custom_path(custom_path: &str) -> Option<CapCutPaths> {
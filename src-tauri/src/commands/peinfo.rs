@@ -0,0 +1,492 @@
+//! PE version resource reader
+//! Extracts VERSIONINFO (file/product version, company) and the link timestamp from Windows executables in pure Rust
+
+use serde::Serialize;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Resource type id of VERSIONINFO
+const RT_VERSION: u32 = 16;
+
+/// Signature of VS_FIXEDFILEINFO
+const FIXED_FILE_INFO_SIGNATURE: u32 = 0xFEEF_04BD;
+
+/// Largest resource section we are willing to read
+const MAX_RESOURCE_SIZE: u32 = 64 * 1024 * 1024;
+
+/// Version metadata embedded in an executable
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PeVersionInfo {
+    /// File version from VS_FIXEDFILEINFO (falls back to the FileVersion string)
+    pub file_version: Option<String>,
+    /// Product version from VS_FIXEDFILEINFO (falls back to the ProductVersion string)
+    pub product_version: Option<String>,
+    pub company: Option<String>,
+    pub product_name: Option<String>,
+    /// Link timestamp from the COFF header (Unix timestamp)
+    pub build_date: Option<u64>,
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn align4(offset: usize) -> usize {
+    (offset + 3) & !3
+}
+
+fn read_at(file: &mut File, offset: u64, len: usize) -> Option<Vec<u8>> {
+    let mut buf = vec![0u8; len];
+    file.seek(SeekFrom::Start(offset)).ok()?;
+    file.read_exact(&mut buf).ok()?;
+    Some(buf)
+}
+
+/// Raw resource section and the RVA it starts at
+struct ResourceSection {
+    data: Vec<u8>,
+    rva: u32,
+}
+
+/// A section header: virtual address range and where its data lives in the file
+struct Section {
+    virtual_address: u32,
+    virtual_size: u32,
+    raw_offset: u32,
+    raw_size: u32,
+}
+
+impl Section {
+    fn contains(&self, rva: u32) -> bool {
+        let size = self.virtual_size.max(self.raw_size);
+        rva >= self.virtual_address && rva < self.virtual_address.saturating_add(size)
+    }
+
+    fn file_offset(&self, rva: u32) -> u64 {
+        (rva - self.virtual_address) as u64 + self.raw_offset as u64
+    }
+}
+
+/// Read version metadata from a PE file
+pub fn read_version_info(path: &Path) -> Result<PeVersionInfo, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let (timestamp, resource) = read_resource_section(&mut file)?;
+
+    let mut info = PeVersionInfo {
+        build_date: (timestamp != 0).then_some(timestamp as u64),
+        ..Default::default()
+    };
+    if let Some(resource) = resource {
+        if let Some(version) = find_version_resource(&resource.data, resource.rva) {
+            parse_version_resource(version, &mut info);
+        }
+    }
+    Ok(info)
+}
+
+/// Locate the resource section. Returns the COFF timestamp and the section, if any.
+fn read_resource_section(file: &mut File) -> Result<(u32, Option<ResourceSection>), String> {
+    let dos = read_at(file, 0, 64).ok_or("File too small to be an executable")?;
+    if &dos[0..2] != b"MZ" {
+        return Err("Not a Windows executable".to_string());
+    }
+    let pe_offset = u32_at(&dos, 0x3C).unwrap_or(0) as u64;
+
+    let header = read_at(file, pe_offset, 24).ok_or("Truncated PE header")?;
+    if &header[0..4] != b"PE\0\0" {
+        return Err("Missing PE signature".to_string());
+    }
+    let section_count = u16_at(&header, 6).unwrap_or(0) as usize;
+    let timestamp = u32_at(&header, 8).unwrap_or(0);
+    let optional_size = u16_at(&header, 20).unwrap_or(0) as usize;
+
+    let optional =
+        read_at(file, pe_offset + 24, optional_size).ok_or("Truncated optional header")?;
+    let (count_offset, dirs_offset) = match u16_at(&optional, 0) {
+        Some(0x10b) => (92, 96),   // PE32
+        Some(0x20b) => (108, 112), // PE32+
+        _ => return Err("Unknown optional header format".to_string()),
+    };
+    let dir_count = u32_at(&optional, count_offset).unwrap_or(0);
+    if dir_count <= 2 {
+        return Ok((timestamp, None));
+    }
+    let resource_rva = u32_at(&optional, dirs_offset + 16).unwrap_or(0);
+    let resource_size = u32_at(&optional, dirs_offset + 20).unwrap_or(0);
+    if resource_rva == 0 || resource_size == 0 || resource_size > MAX_RESOURCE_SIZE {
+        return Ok((timestamp, None));
+    }
+
+    let table_offset = pe_offset + 24 + optional_size as u64;
+    let table = read_at(file, table_offset, section_count * 40).ok_or("Truncated section table")?;
+    let section = table
+        .chunks_exact(40)
+        .map(|s| Section {
+            virtual_size: u32_at(s, 8).unwrap_or(0),
+            virtual_address: u32_at(s, 12).unwrap_or(0),
+            raw_size: u32_at(s, 16).unwrap_or(0),
+            raw_offset: u32_at(s, 20).unwrap_or(0),
+        })
+        .find(|s| s.contains(resource_rva))
+        .ok_or("Resource directory outside any section")?;
+
+    // Resource data entries may point anywhere in the section, so read all of it
+    let start = section.file_offset(resource_rva);
+    let available = (section.raw_offset as u64 + section.raw_size as u64).saturating_sub(start);
+    let len = available.min(MAX_RESOURCE_SIZE as u64) as usize;
+    let data = read_at(file, start, len).ok_or("Truncated resource section")?;
+    Ok((
+        timestamp,
+        Some(ResourceSection {
+            data,
+            rva: resource_rva,
+        }),
+    ))
+}
+
+/// Entries of a resource directory: (id, offset, is subdirectory)
+fn directory_entries(data: &[u8], offset: usize) -> Vec<(u32, usize, bool)> {
+    let named = u16_at(data, offset + 12).unwrap_or(0) as usize;
+    let ids = u16_at(data, offset + 14).unwrap_or(0) as usize;
+    (0..named + ids)
+        .filter_map(|i| {
+            let entry = offset + 16 + i * 8;
+            let id = u32_at(data, entry)?;
+            let target = u32_at(data, entry + 4)?;
+            Some((
+                id,
+                (target & 0x7FFF_FFFF) as usize,
+                target & 0x8000_0000 != 0,
+            ))
+        })
+        .collect()
+}
+
+/// Walk type -> name -> language to the first VERSIONINFO blob
+fn find_version_resource(data: &[u8], base_rva: u32) -> Option<&[u8]> {
+    let (_, names, _) = directory_entries(data, 0)
+        .into_iter()
+        .find(|(id, _, is_dir)| *id == RT_VERSION && *is_dir)?;
+    let (_, langs, _) = directory_entries(data, names)
+        .into_iter()
+        .find(|(_, _, is_dir)| *is_dir)?;
+    let (_, entry, _) = directory_entries(data, langs)
+        .into_iter()
+        .find(|(_, _, is_dir)| !*is_dir)?;
+
+    let rva = u32_at(data, entry)?;
+    let size = u32_at(data, entry + 4)? as usize;
+    let start = rva.checked_sub(base_rva)? as usize;
+    data.get(start..start + size)
+}
+
+/// One node of the VERSIONINFO tree
+struct Block<'a> {
+    key: String,
+    value: &'a [u8],
+    /// Value is UTF-16 text rather than binary
+    is_text: bool,
+    children: &'a [u8],
+}
+
+/// Parse a block at the start of `data`, returning it and its aligned length
+fn read_block(data: &[u8]) -> Option<(Block<'_>, usize)> {
+    let length = u16_at(data, 0)? as usize;
+    let value_length = u16_at(data, 2)? as usize;
+    let is_text = u16_at(data, 4)? == 1;
+    if length < 6 || length > data.len() {
+        return None;
+    }
+    let block = &data[..length];
+
+    let mut key_units = Vec::new();
+    let mut offset = 6;
+    while let Some(unit) = u16_at(block, offset) {
+        offset += 2;
+        if unit == 0 {
+            break;
+        }
+        key_units.push(unit);
+    }
+    let value_start = align4(offset).min(length);
+    let value_bytes = if is_text {
+        value_length * 2
+    } else {
+        value_length
+    };
+    let value_end = (value_start + value_bytes).min(length);
+    let children_start = align4(value_end).min(length);
+
+    Some((
+        Block {
+            key: String::from_utf16_lossy(&key_units),
+            value: &block[value_start..value_end],
+            is_text,
+            children: &block[children_start..],
+        },
+        align4(length),
+    ))
+}
+
+/// All sibling blocks in a byte range
+fn read_blocks(mut data: &[u8]) -> Vec<Block<'_>> {
+    let mut blocks = Vec::new();
+    while let Some((block, len)) = read_block(data) {
+        blocks.push(block);
+        if len >= data.len() {
+            break;
+        }
+        data = &data[len..];
+    }
+    blocks
+}
+
+fn utf16_text(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .take_while(|&u| u != 0)
+        .collect();
+    String::from_utf16_lossy(&units).trim().to_string()
+}
+
+/// Four-part version from a pair of MS/LS dwords
+fn fixed_version(ms: u32, ls: u32) -> String {
+    format!("{}.{}.{}.{}", ms >> 16, ms & 0xFFFF, ls >> 16, ls & 0xFFFF)
+}
+
+fn parse_version_resource(data: &[u8], info: &mut PeVersionInfo) {
+    let Some((root, _)) = read_block(data) else {
+        return;
+    };
+
+    if u32_at(root.value, 0) == Some(FIXED_FILE_INFO_SIGNATURE) {
+        let dword = |i: usize| u32_at(root.value, i * 4).unwrap_or(0);
+        info.file_version = Some(fixed_version(dword(2), dword(3)));
+        info.product_version = Some(fixed_version(dword(4), dword(5)));
+    }
+
+    // StringFileInfo -> string tables (one per language) -> strings
+    for file_info in read_blocks(root.children)
+        .into_iter()
+        .filter(|b| b.key == "StringFileInfo")
+    {
+        for table in read_blocks(file_info.children) {
+            for string in read_blocks(table.children) {
+                let value = utf16_text(string.value);
+                if !string.is_text || value.is_empty() {
+                    continue;
+                }
+                let slot = match string.key.as_str() {
+                    "CompanyName" => &mut info.company,
+                    "ProductName" => &mut info.product_name,
+                    "FileVersion" => &mut info.file_version,
+                    "ProductVersion" => &mut info.product_version,
+                    _ => continue,
+                };
+                slot.get_or_insert(value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::scratch_dir;
+
+    const TIMESTAMP: u32 = 1_700_000_000;
+    /// File offset of the section table in the fixture
+    const SECTION_TABLE: usize = 0x40 + 24 + 224;
+    const RSRC_OFFSET: usize = 0x200;
+    const RSRC_RVA: u32 = 0x1000;
+
+    fn utf16z(text: &str) -> Vec<u8> {
+        text.encode_utf16()
+            .chain([0])
+            .flat_map(|u| u.to_le_bytes())
+            .collect()
+    }
+
+    fn pad4(data: &mut Vec<u8>) {
+        data.resize(align4(data.len()), 0);
+    }
+
+    /// A VERSIONINFO block: header, key, value, then children
+    fn block(key: &str, value: &[u8], is_text: bool, children: &[Vec<u8>]) -> Vec<u8> {
+        let value_length = if is_text {
+            value.len() / 2
+        } else {
+            value.len()
+        };
+        let mut data = vec![0; 6];
+        data[2..4].copy_from_slice(&(value_length as u16).to_le_bytes());
+        data[4..6].copy_from_slice(&(is_text as u16).to_le_bytes());
+        data.extend(utf16z(key));
+        pad4(&mut data);
+        data.extend_from_slice(value);
+        for child in children {
+            pad4(&mut data);
+            data.extend_from_slice(child);
+        }
+        let length = data.len() as u16;
+        data[0..2].copy_from_slice(&length.to_le_bytes());
+        data
+    }
+
+    fn fixed_file_info(ms: u32, ls: u32) -> Vec<u8> {
+        let mut info = vec![0u8; 52];
+        info[0..4].copy_from_slice(&FIXED_FILE_INFO_SIGNATURE.to_le_bytes());
+        for (i, dword) in [ms, ls, ms, ls].iter().enumerate() {
+            info[8 + i * 4..12 + i * 4].copy_from_slice(&dword.to_le_bytes());
+        }
+        info
+    }
+
+    fn version_resource(fixed: &[u8], strings: &[(&str, &str)]) -> Vec<u8> {
+        let strings: Vec<Vec<u8>> = strings
+            .iter()
+            .map(|(key, value)| block(key, &utf16z(value), true, &[]))
+            .collect();
+        let table = block("040904B0", &[], true, &strings);
+        let file_info = block("StringFileInfo", &[], true, &[table]);
+        block("VS_VERSION_INFO", fixed, false, &[file_info])
+    }
+
+    /// Resource directory type -> name -> language -> data entry -> VERSIONINFO
+    fn resource_section(version: &[u8]) -> Vec<u8> {
+        let directory = |id: u32, target: u32| {
+            let mut dir = vec![0u8; 16];
+            dir[14..16].copy_from_slice(&1u16.to_le_bytes());
+            dir.extend(id.to_le_bytes());
+            dir.extend(target.to_le_bytes());
+            dir
+        };
+        let mut data = directory(RT_VERSION, 0x8000_0000 | 0x18);
+        data.extend(directory(1, 0x8000_0000 | 0x30));
+        data.extend(directory(0x409, 0x48));
+        data.extend((RSRC_RVA + 0x58).to_le_bytes());
+        data.extend((version.len() as u32).to_le_bytes());
+        data.extend([0u8; 8]);
+        data.extend_from_slice(version);
+        data
+    }
+
+    /// A minimal PE32 image with one .rsrc section (or none)
+    fn pe_image(resource: Option<&[u8]>) -> Vec<u8> {
+        let mut image = vec![0u8; RSRC_OFFSET];
+        image[0..2].copy_from_slice(b"MZ");
+        image[0x3C..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+
+        let coff = 0x40;
+        image[coff..coff + 4].copy_from_slice(b"PE\0\0");
+        image[coff + 6..coff + 8].copy_from_slice(&1u16.to_le_bytes());
+        image[coff + 8..coff + 12].copy_from_slice(&TIMESTAMP.to_le_bytes());
+        image[coff + 20..coff + 22].copy_from_slice(&224u16.to_le_bytes());
+
+        let optional = coff + 24;
+        image[optional..optional + 2].copy_from_slice(&0x10bu16.to_le_bytes());
+        image[optional + 92..optional + 96].copy_from_slice(&16u32.to_le_bytes());
+
+        let section = SECTION_TABLE;
+        image[section..section + 5].copy_from_slice(b".rsrc");
+        if let Some(resource) = resource {
+            let size = resource.len() as u32;
+            let dir = optional + 96 + 16;
+            image[dir..dir + 4].copy_from_slice(&RSRC_RVA.to_le_bytes());
+            image[dir + 4..dir + 8].copy_from_slice(&size.to_le_bytes());
+            image[section + 8..section + 12].copy_from_slice(&size.to_le_bytes());
+            image[section + 12..section + 16].copy_from_slice(&RSRC_RVA.to_le_bytes());
+            image[section + 16..section + 20].copy_from_slice(&size.to_le_bytes());
+            image[section + 20..section + 24].copy_from_slice(&(RSRC_OFFSET as u32).to_le_bytes());
+            image.extend_from_slice(resource);
+        }
+        image
+    }
+
+    fn read(name: &str, image: &[u8]) -> Result<PeVersionInfo, String> {
+        let path = scratch_dir(name).join("CapCut.exe");
+        std::fs::write(&path, image).unwrap();
+        read_version_info(&path)
+    }
+
+    fn capcut_image() -> Vec<u8> {
+        let version = version_resource(
+            &fixed_file_info(5 << 16 | 4, 1991),
+            &[
+                ("CompanyName", "Bytedance Pte. Ltd."),
+                ("ProductName", "CapCut"),
+                ("FileVersion", "9.9.9.9"),
+            ],
+        );
+        pe_image(Some(&resource_section(&version)))
+    }
+
+    #[test]
+    fn reads_fixed_file_info_and_string_table() {
+        let info = read("pe-full", &capcut_image()).unwrap();
+        // VS_FIXEDFILEINFO wins over the FileVersion string
+        assert_eq!(info.file_version.as_deref(), Some("5.4.0.1991"));
+        assert_eq!(info.product_version.as_deref(), Some("5.4.0.1991"));
+        assert_eq!(info.company.as_deref(), Some("Bytedance Pte. Ltd."));
+        assert_eq!(info.product_name.as_deref(), Some("CapCut"));
+        assert_eq!(info.build_date, Some(TIMESTAMP as u64));
+    }
+
+    #[test]
+    fn falls_back_to_version_strings_without_fixed_info() {
+        let version = version_resource(
+            &[],
+            &[("FileVersion", "3.2.0.100"), ("ProductVersion", "3.2.0")],
+        );
+        let info = read("pe-strings", &pe_image(Some(&resource_section(&version)))).unwrap();
+        assert_eq!(info.file_version.as_deref(), Some("3.2.0.100"));
+        assert_eq!(info.product_version.as_deref(), Some("3.2.0"));
+        assert_eq!(info.company, None);
+    }
+
+    #[test]
+    fn executable_without_resources_has_only_a_build_date() {
+        let info = read("pe-no-rsrc", &pe_image(None)).unwrap();
+        assert_eq!(info.file_version, None);
+        assert_eq!(info.company, None);
+        assert_eq!(info.build_date, Some(TIMESTAMP as u64));
+    }
+
+    #[test]
+    fn truncated_headers_are_errors() {
+        let image = capcut_image();
+        assert!(read("pe-empty", &[]).is_err());
+        assert!(read("pe-not-mz", &image[1..]).is_err());
+        for len in [
+            0x3F,
+            0x40 + 10,
+            0x40 + 100,
+            SECTION_TABLE + 20,
+            RSRC_OFFSET + 8,
+        ] {
+            assert!(
+                read("pe-truncated", &image[..len]).is_err(),
+                "{} bytes",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn corrupt_resource_bytes_never_panic() {
+        let image = capcut_image();
+        for offset in RSRC_OFFSET..image.len() {
+            let mut corrupt = image.clone();
+            corrupt[offset] = 0xFF;
+            let _ = read("pe-corrupt", &corrupt);
+        }
+    }
+}
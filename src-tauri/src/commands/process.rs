@@ -1,10 +1,12 @@
 //! Process detection functionality
 //! Migrated from original eframe/egui main.rs
 
+use std::cmp::Reverse;
 use std::process::Command;
 use sysinfo::System;
 
 use super::paths;
+use super::version::CapCutVersion;
 
 /// Check if CapCut is currently running
 #[tauri::command]
@@ -76,7 +78,11 @@ pub fn launch_capcut() -> LaunchResult {
 
     // Sort to get the latest version
     let mut version_paths: Vec<_> = versions.iter().map(|e| e.path()).collect();
-    version_paths.sort_by(|a, b| b.file_name().cmp(&a.file_name()));
+    version_paths.sort_by_cached_key(|p| {
+        Reverse(CapCutVersion::parse(
+            &p.file_name().unwrap_or_default().to_string_lossy(),
+        ))
+    });
 
     for version_path in version_paths {
         let exe_path = version_path.join("CapCut.exe");
@@ -0,0 +1,397 @@
+//! Typed model of the launcher's ProductInfo.xml
+//! Reads the real file, edits known fields and writes valid XML without dropping unknown elements
+
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const ROOT_ELEMENT: &str = "ProductInfo";
+const INSTALL_PATH: &str = "InstallPath";
+const VERSION: &str = "Version";
+
+/// A direct child of the root element
+#[derive(Debug, Clone)]
+struct Child {
+    /// Element name (None for comments and processing instructions)
+    name: Option<String>,
+    /// Text content of a plain text-only element
+    text: Option<String>,
+    /// Verbatim XML, written back unchanged until the element is edited
+    raw: Option<String>,
+}
+
+/// Parsed ProductInfo.xml
+#[derive(Debug, Clone)]
+pub struct ProductInfo {
+    root: String,
+    /// Root element attributes, kept verbatim
+    root_attributes: String,
+    children: Vec<Child>,
+}
+
+/// Where the launcher currently points
+#[derive(Debug, Clone, Serialize)]
+pub struct LauncherTarget {
+    pub version: Option<String>,
+    pub install_path: Option<String>,
+    /// Whether the executable named by InstallPath exists
+    pub exe_exists: bool,
+}
+
+impl Default for ProductInfo {
+    fn default() -> Self {
+        ProductInfo {
+            root: ROOT_ELEMENT.to_string(),
+            root_attributes: String::new(),
+            children: Vec::new(),
+        }
+    }
+}
+
+/// Path of ProductInfo.xml inside the Apps folder
+pub fn product_info_path(apps_path: &Path) -> PathBuf {
+    apps_path.join("ProductInfo.xml")
+}
+
+fn element_name(e: &BytesStart) -> String {
+    String::from_utf8_lossy(e.name().as_ref()).to_string()
+}
+
+impl ProductInfo {
+    /// An empty document with just the root element
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse XML text
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut reader = Reader::from_str(text);
+        reader.config_mut().trim_text(true);
+
+        let mut info: Option<ProductInfo> = None;
+        let mut depth = 0usize;
+        // Child element being read: (name, start offset, text, is plain text)
+        let mut current: Option<(String, usize, String, bool)> = None;
+
+        loop {
+            let start = reader.buffer_position() as usize;
+            let event = reader
+                .read_event()
+                .map_err(|e| format!("Invalid XML at byte {}: {}", start, e))?;
+            let end = reader.buffer_position() as usize;
+
+            match event {
+                Event::Start(e) | Event::Empty(e) if depth == 0 => {
+                    if info.is_some() {
+                        return Err("Multiple root elements".to_string());
+                    }
+                    info = Some(ProductInfo {
+                        root: element_name(&e),
+                        root_attributes: String::from_utf8_lossy(e.attributes_raw())
+                            .trim()
+                            .to_string(),
+                        children: Vec::new(),
+                    });
+                    if text[start..end].trim_end().ends_with("/>") {
+                        continue;
+                    }
+                    depth = 1;
+                }
+                Event::Start(e) => {
+                    if depth == 1 {
+                        let plain = e.attributes_raw().iter().all(u8::is_ascii_whitespace);
+                        current = Some((element_name(&e), start, String::new(), plain));
+                    } else if let Some(c) = current.as_mut() {
+                        c.3 = false;
+                    }
+                    depth += 1;
+                }
+                Event::Empty(e) => {
+                    if depth == 1 {
+                        if let Some(info) = info.as_mut() {
+                            info.children.push(Child {
+                                name: Some(element_name(&e)),
+                                text: None,
+                                raw: Some(text[start..end].trim().to_string()),
+                            });
+                        }
+                    } else if let Some(c) = current.as_mut() {
+                        c.3 = false;
+                    }
+                }
+                Event::Text(t) if depth == 2 => {
+                    if let Some(c) = current.as_mut() {
+                        let value = t.unescape().map_err(|e| e.to_string())?;
+                        c.2.push_str(&value);
+                    }
+                }
+                Event::Comment(_) | Event::PI(_) if depth == 1 => {
+                    if let Some(info) = info.as_mut() {
+                        info.children.push(Child {
+                            name: None,
+                            text: None,
+                            raw: Some(text[start..end].trim().to_string()),
+                        });
+                    }
+                }
+                Event::CData(_) if depth == 2 => {
+                    if let Some(c) = current.as_mut() {
+                        c.3 = false;
+                    }
+                }
+                Event::End(_) => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 1 {
+                        if let (Some((name, child_start, value, plain)), Some(info)) =
+                            (current.take(), info.as_mut())
+                        {
+                            info.children.push(Child {
+                                name: Some(name),
+                                text: plain.then_some(value),
+                                raw: Some(text[child_start..end].trim().to_string()),
+                            });
+                        }
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        if depth != 0 {
+            return Err("Unexpected end of document".to_string());
+        }
+        info.ok_or_else(|| "No root element".to_string())
+    }
+
+    /// Read and parse a ProductInfo.xml file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|e| format!("Could not read ProductInfo.xml: {}", e))?;
+        Self::parse_bytes(&bytes)
+    }
+
+    /// Parse raw ProductInfo.xml bytes
+    pub fn parse_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let bytes = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes);
+        if bytes.iter().all(u8::is_ascii_whitespace) {
+            return Err("ProductInfo.xml is empty (update blocker)".to_string());
+        }
+        let text = std::str::from_utf8(bytes)
+            .map_err(|_| "ProductInfo.xml is not UTF-8 text".to_string())?;
+        Self::parse(text).map_err(|e| format!("Invalid ProductInfo.xml: {}", e))
+    }
+
+    /// Text of a plain child element
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.children
+            .iter()
+            .find(|c| c.name.as_deref() == Some(name))
+            .and_then(|c| c.text.as_deref())
+    }
+
+    /// Set the text of a child element, adding it if missing.
+    /// Attributes and nested markup of an existing element are replaced.
+    pub fn set(&mut self, name: &str, value: &str) {
+        match self
+            .children
+            .iter_mut()
+            .find(|c| c.name.as_deref() == Some(name))
+        {
+            Some(child) => {
+                if child.text.as_deref() != Some(value) || child.raw.is_none() {
+                    child.text = Some(value.to_string());
+                    child.raw = None;
+                }
+            }
+            None => self.children.push(Child {
+                name: Some(name.to_string()),
+                text: Some(value.to_string()),
+                raw: None,
+            }),
+        }
+    }
+
+    pub fn install_path(&self) -> Option<&str> {
+        self.get(INSTALL_PATH)
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.get(VERSION)
+    }
+
+    /// Point the launcher at an executable and version
+    pub fn set_target(&mut self, exe_path: &Path, version: &str) {
+        self.set(INSTALL_PATH, &exe_path.to_string_lossy());
+        self.set(VERSION, version);
+    }
+
+    /// Version the launcher will start: the Version element, or the folder of InstallPath
+    pub fn active_version(&self) -> Option<String> {
+        self.version().map(str::to_string).or_else(|| {
+            // Split manually: the path is a Windows path whatever platform parses it
+            self.install_path()?
+                .rsplit(['\\', '/'])
+                .filter(|part| !part.is_empty())
+                .nth(1)
+                .map(str::to_string)
+        })
+    }
+
+    /// Summary of where the launcher points
+    pub fn target(&self) -> LauncherTarget {
+        LauncherTarget {
+            version: self.active_version(),
+            install_path: self.install_path().map(str::to_string),
+            exe_exists: self
+                .install_path()
+                .map(|p| Path::new(p).is_file())
+                .unwrap_or(false),
+        }
+    }
+
+    /// Serialize as a UTF-8 XML document
+    pub fn to_xml(&self) -> String {
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\r\n");
+        out.push('<');
+        out.push_str(&self.root);
+        if !self.root_attributes.is_empty() {
+            out.push(' ');
+            out.push_str(&self.root_attributes);
+        }
+        out.push_str(">\r\n");
+
+        for child in &self.children {
+            out.push_str("  ");
+            match (&child.raw, &child.name, &child.text) {
+                (Some(raw), _, _) => out.push_str(raw),
+                (None, Some(name), Some(text)) => {
+                    out.push_str(&format!("<{0}>{1}</{0}>", name, escape(text.as_str())));
+                }
+                _ => {}
+            }
+            out.push_str("\r\n");
+        }
+
+        out.push_str(&format!("</{}>\r\n", self.root));
+        out
+    }
+}
+
+/// Read where the launcher currently points (None if ProductInfo.xml is missing or a blocker)
+pub fn launcher_target(apps_path: &Path) -> Option<LauncherTarget> {
+    ProductInfo::load(&product_info_path(apps_path))
+        .ok()
+        .map(|info| info.target())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::scratch_dir;
+
+    /// Shaped like the file the CapCut launcher writes, with elements Guard does not model
+    const SAMPLE: &str = "\u{feff}<?xml version=\"1.0\" encoding=\"utf-8\"?>\r\n\
+        <ProductInfo xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\">\r\n\
+        \x20 <!-- written by the launcher -->\r\n\
+        \x20 <InstallPath>C:\\Users\\me\\AppData\\Local\\CapCut\\Apps\\5.4.0.1991\\CapCut.exe</InstallPath>\r\n\
+        \x20 <Version>5.4.0.1991</Version>\r\n\
+        \x20 <Channel type=\"beta\">beta6</Channel>\r\n\
+        \x20 <Updater><Enabled>true</Enabled><Url>https://example.com/update?a=1&amp;b=2</Url></Updater>\r\n\
+        \x20 <Flags />\r\n\
+        </ProductInfo>\r\n";
+
+    fn write_sample(name: &str, content: &str) -> PathBuf {
+        let apps = scratch_dir(name).join("Apps");
+        fs::create_dir_all(&apps).unwrap();
+        fs::write(product_info_path(&apps), content).unwrap();
+        apps
+    }
+
+    #[test]
+    fn reads_known_fields_of_the_launcher_file() {
+        let apps = write_sample("productinfo-read", SAMPLE);
+        let info = ProductInfo::load(&product_info_path(&apps)).unwrap();
+        assert_eq!(info.version(), Some("5.4.0.1991"));
+        assert!(info
+            .install_path()
+            .unwrap()
+            .ends_with("\\5.4.0.1991\\CapCut.exe"));
+        // Elements with attributes or children are kept but not read as text
+        assert_eq!(info.get("Channel"), None);
+        assert_eq!(info.get("Updater"), None);
+    }
+
+    #[test]
+    fn set_target_keeps_unknown_elements_and_writes_valid_xml() {
+        let mut info = ProductInfo::parse(SAMPLE.trim_start_matches('\u{feff}')).unwrap();
+        info.set_target(
+            Path::new("C:\\CapCut & Co\\Apps\\3.9.0.1459\\CapCut.exe"),
+            "3.9.0.1459",
+        );
+        let xml = info.to_xml();
+
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>"));
+        assert!(
+            xml.contains("<ProductInfo xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\">")
+        );
+        for kept in [
+            "<!-- written by the launcher -->",
+            "<Channel type=\"beta\">beta6</Channel>",
+            "<Updater><Enabled>true</Enabled><Url>https://example.com/update?a=1&amp;b=2</Url></Updater>",
+            "<Flags />",
+        ] {
+            assert!(xml.contains(kept), "{} missing from\n{}", kept, xml);
+        }
+        assert!(xml.contains(
+            "<InstallPath>C:\\CapCut &amp; Co\\Apps\\3.9.0.1459\\CapCut.exe</InstallPath>"
+        ));
+
+        let reparsed = ProductInfo::parse(&xml).unwrap();
+        assert_eq!(reparsed.version(), Some("3.9.0.1459"));
+        assert_eq!(
+            reparsed.install_path(),
+            Some("C:\\CapCut & Co\\Apps\\3.9.0.1459\\CapCut.exe")
+        );
+        // A second round trip changes nothing
+        assert_eq!(reparsed.to_xml(), xml);
+    }
+
+    #[test]
+    fn launcher_target_reports_the_active_version_and_exe() {
+        let dir = scratch_dir("productinfo-target");
+        let apps = dir.join("Apps");
+        let exe = apps.join("5.3.0.1964").join("CapCut.exe");
+        fs::create_dir_all(exe.parent().unwrap()).unwrap();
+        fs::write(&exe, b"MZ").unwrap();
+
+        let mut info = ProductInfo::parse(SAMPLE.trim_start_matches('\u{feff}')).unwrap();
+        info.set_target(&exe, "5.3.0.1964");
+        fs::write(product_info_path(&apps), info.to_xml()).unwrap();
+        let target = launcher_target(&apps).unwrap();
+        assert_eq!(target.version.as_deref(), Some("5.3.0.1964"));
+        assert_eq!(target.install_path, Some(exe.to_string_lossy().to_string()));
+        assert!(target.exe_exists);
+
+        // Without a Version element the version folder of InstallPath is used
+        let sample = SAMPLE.replace("  <Version>5.4.0.1991</Version>\r\n", "");
+        let apps = write_sample("productinfo-folder", &sample);
+        let target = launcher_target(&apps).unwrap();
+        assert_eq!(target.version.as_deref(), Some("5.4.0.1991"));
+        assert!(!target.exe_exists);
+    }
+
+    #[test]
+    fn blockers_and_broken_files_have_no_target() {
+        let apps = write_sample("productinfo-blocker", "");
+        let err = ProductInfo::load(&product_info_path(&apps)).unwrap_err();
+        assert!(err.contains("update blocker"), "{}", err);
+        assert!(launcher_target(&apps).is_none());
+
+        assert!(ProductInfo::parse("<ProductInfo><Version>1</ProductInfo>").is_err());
+        assert!(ProductInfo::parse("<ProductInfo><Version>1</Version>").is_err());
+        assert!(ProductInfo::parse("<!-- nothing -->").is_err());
+    }
+}
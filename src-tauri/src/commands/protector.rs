@@ -1,88 +1,35 @@
 //! Protection and file locking functionality
 //! Migrated from original eframe/egui main.rs
 
-use std::fs;
-use std::path::{Path, PathBuf};
-use std::process::Command;
-use walkdir::WalkDir;
+use std::path::PathBuf;
 
+use super::fsops::{ChangePlan, FsOps};
+use super::manifest::ProtectionManifest;
 use super::paths;
-
-/// Unset readonly attribute recursively
-fn unset_readonly_recursive(path: &Path) -> Result<(), String> {
-    for entry in WalkDir::new(path).into_iter().filter_map(|e| e.ok()) {
-        let p = entry.path();
-        if let Ok(meta) = fs::metadata(p) {
-            let mut perms = meta.permissions();
-            if perms.readonly() {
-                perms.set_readonly(false);
-                fs::set_permissions(p, perms).ok();
-            }
-        }
-    }
-    Ok(())
-}
-
-/// Create readonly lock file
-fn create_readonly(path: &Path) -> Result<(), String> {
-    if path.exists() {
-        unset_readonly_recursive(path).ok();
-        if path.is_dir() {
-            fs::remove_dir_all(path).map_err(|e| e.to_string())?;
-        } else {
-            fs::remove_file(path).map_err(|e| e.to_string())?;
-        }
-    }
-    fs::write(path, "").map_err(|e| e.to_string())?;
-    Command::new("attrib")
-        .arg("+r")
-        .arg(path)
-        .output()
-        .map_err(|e| e.to_string())?;
-    Ok(())
+use super::productinfo::{self, LauncherTarget};
+use super::strategy::{self, StrategyContext, StrategySpec, StrategyStatus};
+use super::transaction::{SystemState, Transaction, TransactionResult, UndoAction};
+use super::watcher;
+
+/// Resolve the CapCut locations strategies operate on
+pub(crate) fn strategy_context() -> Result<StrategyContext, String> {
+    let capcut_paths = paths::resolve_capcut_paths().ok_or("Could not find CapCut installation")?;
+    Ok(StrategyContext {
+        capcut_root: capcut_paths.root,
+        apps_path: capcut_paths.apps,
+    })
 }
 
-/// Lock configuration file
-fn lock_configuration(apps_path: &Path) -> Result<(), String> {
-    let config_path = apps_path.join("configure.ini");
-    let content = if config_path.exists() {
-        fs::read_to_string(&config_path).unwrap_or_default()
-    } else {
-        String::new()
-    };
-
-    let mut new_lines: Vec<String> = Vec::new();
-    let mut found = false;
-
-    for line in content.lines() {
-        if line.trim().starts_with("last_version") {
-            new_lines.push("last_version=1.0.0.0".to_string());
-            found = true;
-        } else {
-            new_lines.push(line.to_string());
-        }
+/// Strategy specs equivalent to the legacy lock_config / create_blockers flags
+fn legacy_specs(lock_config: bool, create_blockers: bool) -> Vec<StrategySpec> {
+    let mut specs = Vec::new();
+    if lock_config {
+        specs.push(StrategySpec::new("config_lock"));
     }
-
-    if !found {
-        new_lines.push("last_version=1.0.0.0".to_string());
+    if create_blockers {
+        specs.push(StrategySpec::new("blocker_files"));
     }
-
-    fs::write(config_path, new_lines.join("\n")).map_err(|e| e.to_string())?;
-    Ok(())
-}
-
-/// Create dummy lock files
-fn create_dummy_files(capcut_path: &Path, apps_path: &Path) -> Result<(), String> {
-    let pinfo = apps_path.join("ProductInfo.xml");
-    create_readonly(&pinfo)?;
-
-    let download_dir = capcut_path.join("User Data").join("Download");
-    fs::create_dir_all(&download_dir).map_err(|e| e.to_string())?;
-
-    let update_exe = download_dir.join("update.exe");
-    create_readonly(&update_exe)?;
-
-    Ok(())
+    specs
 }
 
 /// Protection result
@@ -96,17 +43,38 @@ pub struct ProtectionResult {
 /// Delete specified version directories (with automatic backup)
 #[tauri::command]
 pub fn delete_versions(paths: Vec<String>) -> ProtectionResult {
-    use super::backup;
+    let mut logs: Vec<String> = Vec::new();
+    let result = delete_versions_with(&paths, &mut FsOps::execute(), &mut logs);
 
+    ProtectionResult {
+        success: result.is_ok(),
+        error: result.err(),
+        logs,
+    }
+}
+
+/// Plan version deletion without touching disk
+#[tauri::command]
+pub fn plan_delete_versions(paths: Vec<String>) -> ChangePlan {
+    let mut ops = FsOps::plan();
     let mut logs: Vec<String> = Vec::new();
+    let result = delete_versions_with(&paths, &mut ops, &mut logs);
+    ops.into_plan(result, logs)
+}
 
-    for path_str in &paths {
+/// Delete version directories, backing each one up first (best effort)
+fn delete_versions_with(
+    paths: &[String],
+    ops: &mut FsOps,
+    logs: &mut Vec<String>,
+) -> Result<(), String> {
+    for path_str in paths {
         let path = PathBuf::from(path_str);
         let name = path.file_name().unwrap_or_default().to_string_lossy();
 
         // Create backup before deletion
         logs.push(format!("Backing up: {}", name));
-        let backup_result = backup::create_backup(&path, "Version deleted during protection");
+        let backup_result = ops.backup(&path, "Version deleted during protection");
 
         if backup_result.success {
             if let Some(backup_id) = &backup_result.backup_id {
@@ -122,18 +90,8 @@ pub fn delete_versions(paths: Vec<String>) -> ProtectionResult {
         }
 
         logs.push(format!("Deleting: {}", name));
-
-        if let Err(e) = unset_readonly_recursive(&path) {
-            logs.push(format!("[!] Warning: {}", e));
-        }
-
-        if let Err(e) = fs::remove_dir_all(&path) {
-            return ProtectionResult {
-                success: false,
-                error: Some(format!("Failed to delete {}: {}", name, e)),
-                logs,
-            };
-        }
+        ops.remove(&path)
+            .map_err(|e| format!("Failed to delete {}: {}", name, e))?;
     }
 
     if paths.is_empty() {
@@ -143,112 +101,125 @@ pub fn delete_versions(paths: Vec<String>) -> ProtectionResult {
         logs.push("[OK] Backups available for recovery".to_string());
     }
 
-    ProtectionResult {
-        success: true,
-        error: None,
-        logs,
-    }
+    Ok(())
 }
 
 /// Apply protection (lock config + create locks)
 #[tauri::command]
 pub fn apply_protection() -> ProtectionResult {
-    let capcut_paths = match paths::resolve_capcut_paths() {
-        Some(p) => p,
-        None => {
-            return ProtectionResult {
-                success: false,
-                error: Some("Could not find CapCut installation".to_string()),
-                logs: vec![],
-            }
-        }
-    };
-
-    let apps_path = capcut_paths.apps;
-    let capcut_root = capcut_paths.root;
-    let mut logs: Vec<String> = Vec::new();
-
-    // Lock configuration
-    logs.push("Modifying config...".to_string());
-    if let Err(e) = lock_configuration(&apps_path) {
-        return ProtectionResult {
-            success: false,
-            error: Some(e),
-            logs,
-        };
-    }
-    logs.push("[OK] Configuration locked".to_string());
-
-    // Create locks
-    logs.push("Creating locks...".to_string());
-    if let Err(e) = create_dummy_files(&capcut_root, &apps_path) {
-        return ProtectionResult {
-            success: false,
-            error: Some(e),
-            logs,
-        };
-    }
-    logs.push("[OK] Version lock active".to_string());
-
-    ProtectionResult {
-        success: true,
-        error: None,
-        logs,
-    }
+    let _paused = watcher::pause();
+    apply_protection_with_options(true, true)
 }
 
 /// Apply protection with specific options
 pub fn apply_protection_with_options(lock_config: bool, create_blockers: bool) -> ProtectionResult {
-    let capcut_paths = match paths::resolve_capcut_paths() {
-        Some(p) => p,
-        None => {
+    apply_strategies(&legacy_specs(lock_config, create_blockers))
+}
+
+/// Apply an ordered list of protection strategies
+pub fn apply_strategies(specs: &[StrategySpec]) -> ProtectionResult {
+    let ctx = match strategy_context() {
+        Ok(c) => c,
+        Err(e) => {
             return ProtectionResult {
                 success: false,
-                error: Some("Could not find CapCut installation".to_string()),
+                error: Some(e),
                 logs: vec![],
             }
         }
     };
-
-    let apps_path = capcut_paths.apps;
-    let capcut_root = capcut_paths.root;
     let mut logs: Vec<String> = Vec::new();
 
-    // Lock configuration if enabled
-    if lock_config {
-        logs.push("Modifying config...".to_string());
-        if let Err(e) = lock_configuration(&apps_path) {
+    let mut manifest = match ProtectionManifest::open_default() {
+        Ok(m) => m,
+        Err(e) => {
             return ProtectionResult {
                 success: false,
                 error: Some(e),
                 logs,
-            };
+            }
         }
-        logs.push("[OK] Configuration locked".to_string());
-    } else {
-        logs.push("Skipping config lock (disabled)".to_string());
-    }
+    };
 
-    // Create locks if enabled
-    if create_blockers {
-        logs.push("Creating locks...".to_string());
-        if let Err(e) = create_dummy_files(&capcut_root, &apps_path) {
-            return ProtectionResult {
-                success: false,
-                error: Some(e),
-                logs,
-            };
+    let mut ops = FsOps::execute();
+    let mut result = Ok(());
+    for spec in specs {
+        result = apply_strategy(&ctx, spec, &mut manifest, &mut ops, &mut logs);
+        if result.is_err() {
+            break;
         }
-        logs.push("[OK] Version lock active".to_string());
-    } else {
-        logs.push("Skipping lock creation (disabled)".to_string());
     }
 
-    ProtectionResult {
-        success: true,
-        error: None,
-        logs,
+    // Persist the manifest even on failure - earlier steps may already have run
+    if let Err(e) = manifest.save() {
+        logs.push(format!("[!] {}", e));
+    } else if !manifest.entries.is_empty() {
+        logs.push(format!(
+            "[OK] Recorded {} original file(s) for restore",
+            manifest.entries.len()
+        ));
     }
+
+    match result {
+        Ok(()) => ProtectionResult {
+            success: true,
+            error: None,
+            logs,
+        },
+        Err(e) => ProtectionResult {
+            success: false,
+            error: Some(e),
+            logs,
+        },
+    }
+}
+
+/// Apply a single strategy, recording touched files in the manifest
+pub(crate) fn apply_strategy(
+    ctx: &StrategyContext,
+    spec: &StrategySpec,
+    manifest: &mut ProtectionManifest,
+    ops: &mut FsOps,
+    logs: &mut Vec<String>,
+) -> Result<(), String> {
+    let strategy = strategy::strategy_by_id(&spec.id)
+        .ok_or_else(|| format!("Unknown protection strategy: {}", spec.id))?;
+    logs.push(format!("Applying {}...", strategy.name()));
+    strategy.apply(ctx, &spec.options, manifest, ops)?;
+    manifest.mark_applied(spec);
+    logs.push(format!("[OK] {} active", strategy.name()));
+    Ok(())
+}
+
+/// Temporarily revert a strategy so another operation can rewrite its files
+pub(crate) fn lift_strategy(
+    ctx: &StrategyContext,
+    spec: &StrategySpec,
+    manifest: &mut ProtectionManifest,
+    ops: &mut FsOps,
+    logs: &mut Vec<String>,
+) -> Result<(), String> {
+    let strategy = strategy::strategy_by_id(&spec.id)
+        .ok_or_else(|| format!("Unknown protection strategy: {}", spec.id))?;
+    logs.push(format!("Lifting {}...", strategy.name()));
+    strategy.revert(ctx, &spec.options, manifest, ops, logs)
+}
+
+/// Strategies currently in place, with the options they were applied with
+pub(crate) fn active_specs(
+    ctx: &StrategyContext,
+    manifest: &ProtectionManifest,
+) -> Vec<StrategySpec> {
+    strategy::all_strategies()
+        .iter()
+        .filter_map(|s| {
+            let options = manifest.applied_options(s.id());
+            s.verify(ctx, &options).active.then(|| StrategySpec {
+                id: s.id().to_string(),
+                options,
+            })
+        })
+        .collect()
 }
 
 /// Full protection sequence
@@ -256,214 +227,535 @@ pub fn apply_protection_with_options(lock_config: bool, create_blockers: bool) -
 pub struct ProtectionParams {
     pub versions_to_delete: Vec<String>,
     pub clean_cache: bool,
+    #[serde(default)]
     pub lock_config: bool,
+    #[serde(default)]
     pub create_blockers: bool,
+    /// Ordered strategies to apply; when absent the legacy flags above are used
+    #[serde(default)]
+    pub strategies: Option<Vec<StrategySpec>>,
 }
 
-#[tauri::command]
-pub fn run_full_protection(params: ProtectionParams) -> ProtectionResult {
+impl ProtectionParams {
+    /// Strategies selected for this run
+    pub fn strategy_specs(&self) -> Vec<StrategySpec> {
+        match &self.strategies {
+            Some(specs) => specs.clone(),
+            None => legacy_specs(self.lock_config, self.create_blockers),
+        }
+    }
+}
+
+/// Delete versions inside a transaction - every deletion must be backed up first
+fn delete_versions_transactional(
+    paths: &[String],
+    txn: &mut Transaction,
+    ops: &mut FsOps,
+    logs: &mut Vec<String>,
+) -> Result<(), String> {
+    for path_str in paths {
+        let path = PathBuf::from(path_str);
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+
+        logs.push(format!("Backing up: {}", name));
+        let backup_result = ops.backup(&path, "Version deleted during protection");
+        let backup_id = match backup_result.backup_id {
+            Some(id) if backup_result.success => id,
+            _ => {
+                // Without a backup this deletion could not be rolled back
+                return Err(format!(
+                    "Backup of {} failed: {}",
+                    name,
+                    backup_result.error.unwrap_or_default()
+                ));
+            }
+        };
+        logs.push(format!("[OK] Backup created: {}", backup_id));
+
+        // Registered before deleting so a partial removal is restored too
+        txn.register(
+            format!("delete {}", name),
+            UndoAction::RestoreBackup { backup_id },
+        )?;
+
+        logs.push(format!("Deleting: {}", name));
+        ops.remove(&path)
+            .map_err(|e| format!("Failed to delete {}: {}", name, e))?;
+    }
+
+    if paths.is_empty() {
+        logs.push("[OK] No versions to delete".to_string());
+    } else {
+        logs.push(format!("[OK] Deleted {} version(s)", paths.len()));
+    }
+    Ok(())
+}
+
+/// Run the reversible protection steps, registering an undo action for each
+fn run_protection_steps(
+    params: &ProtectionParams,
+    txn: &mut Transaction,
+    manifest: &mut ProtectionManifest,
+    ops: &mut FsOps,
+    logs: &mut Vec<String>,
+) -> Result<(), String> {
     use crate::commands::cleaner;
+
+    delete_versions_transactional(&params.versions_to_delete, txn, ops, logs)?;
+
+    let specs = params.strategy_specs();
+    if specs.is_empty() {
+        logs.push("Skipping protection (all options disabled)".to_string());
+    } else {
+        let ctx = strategy_context()?;
+        for spec in &specs {
+            let strategy = strategy::strategy_by_id(&spec.id)
+                .ok_or_else(|| format!("Unknown protection strategy: {}", spec.id))?;
+            let files = strategy.touched_paths(&ctx, &spec.options);
+            txn.snapshot(&files, ops)?;
+            txn.register(strategy.name(), UndoAction::RestoreFiles { paths: files })?;
+            apply_strategy(&ctx, spec, manifest, ops, logs)?;
+        }
+    }
+
+    // Last, because deleted cache files are not restored if this step fails
+    if params.clean_cache {
+        logs.push("Cleaning cache directories (not undone on rollback)...".to_string());
+        cleaner::clean_cache_with(ops, logs)?;
+    } else {
+        logs.push("Skipping cache cleaning (disabled)".to_string());
+    }
+
+    Ok(())
+}
+
+/// Run the full protection sequence as a transaction.
+/// If any step fails, every step that already ran is rolled back.
+#[tauri::command]
+pub fn run_full_protection(params: ProtectionParams) -> TransactionResult {
     use crate::commands::process;
 
+    let _paused = watcher::pause();
     let mut all_logs: Vec<String> = Vec::new();
 
+    let fail_early = |error: String, logs: Vec<String>| TransactionResult {
+        success: false,
+        error: Some(error),
+        logs,
+        final_state: SystemState::Original,
+        rollback_errors: vec![],
+    };
+
     // Check if CapCut is running
     all_logs.push("Checking system state...".to_string());
     if process::is_capcut_running() {
-        return ProtectionResult {
-            success: false,
-            error: Some("CapCut is still running. Please close it.".to_string()),
-            logs: all_logs,
-        };
+        return fail_early(
+            "CapCut is still running. Please close it.".to_string(),
+            all_logs,
+        );
     }
     all_logs.push("[OK] No running instances".to_string());
 
-    // Delete versions
-    let delete_result = delete_versions(params.versions_to_delete);
-    all_logs.extend(delete_result.logs);
-    if !delete_result.success {
-        return ProtectionResult {
+    let mut txn = match Transaction::begin(&mut all_logs) {
+        Ok(t) => t,
+        Err(e) => return fail_early(e, all_logs),
+    };
+    let mut manifest = match ProtectionManifest::open_default() {
+        Ok(m) => m,
+        Err(e) => return fail_early(e, all_logs),
+    };
+    let recorded_before = manifest.entries.len();
+    let applied_before = manifest.applied.clone();
+
+    if let Err(e) = run_protection_steps(
+        &params,
+        &mut txn,
+        &mut manifest,
+        &mut FsOps::execute(),
+        &mut all_logs,
+    ) {
+        all_logs.push(format!("[!] {}", e));
+        let rollback_errors = txn.rollback(&mut all_logs);
+
+        // Forget snapshots taken by the rolled-back steps
+        manifest.truncate(recorded_before);
+        manifest.applied = applied_before;
+        let manifest_result = if manifest.is_empty() {
+            manifest.clear()
+        } else {
+            manifest.save()
+        };
+        if let Err(me) = manifest_result {
+            all_logs.push(format!("[!] Could not update protection manifest: {}", me));
+        }
+
+        let final_state = if rollback_errors.is_empty() {
+            all_logs.push("[OK] Rolled back - system is in its original state".to_string());
+            SystemState::Original
+        } else {
+            all_logs.push("[!] Rollback incomplete - manual recovery may be needed".to_string());
+            SystemState::Inconsistent
+        };
+
+        return TransactionResult {
             success: false,
-            error: delete_result.error,
+            error: Some(e),
             logs: all_logs,
+            final_state,
+            rollback_errors,
         };
     }
 
-    // Clean cache if enabled
-    if params.clean_cache {
-        all_logs.push("Cleaning cache directories...".to_string());
-        let cache_result = cleaner::clean_cache();
-        all_logs.extend(cache_result.logs);
-    } else {
-        all_logs.push("Skipping cache cleaning (disabled)".to_string());
+    if let Err(e) = manifest.save() {
+        all_logs.push(format!("[!] {}", e));
     }
-
-    // Apply protection (conditionally based on flags)
-    if params.lock_config || params.create_blockers {
-        let protect_result =
-            apply_protection_with_options(params.lock_config, params.create_blockers);
-        all_logs.extend(protect_result.logs);
-        if !protect_result.success {
-            return ProtectionResult {
-                success: false,
-                error: protect_result.error,
-                logs: all_logs,
-            };
-        }
-    } else {
-        all_logs.push("Skipping protection (all options disabled)".to_string());
+    txn.commit();
+    if !params.versions_to_delete.is_empty() {
+        all_logs.push("[OK] Backups available for recovery".to_string());
     }
 
-    ProtectionResult {
+    TransactionResult {
         success: true,
         error: None,
         logs: all_logs,
+        final_state: SystemState::Target,
+        rollback_errors: vec![],
     }
 }
 
+/// Plan the full protection sequence without touching disk
+#[tauri::command]
+pub fn plan_full_protection(params: ProtectionParams) -> ChangePlan {
+    let mut ops = FsOps::plan();
+    let mut logs: Vec<String> = Vec::new();
+    let mut txn = Transaction::planned();
+    let mut manifest = match ProtectionManifest::open_default() {
+        Ok(m) => m,
+        Err(e) => return ops.into_plan(Err(e), logs),
+    };
+
+    let result = run_protection_steps(&params, &mut txn, &mut manifest, &mut ops, &mut logs);
+    ops.into_plan(result, logs)
+}
+
+/// Overall protection verdict
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtectionVerdict {
+    /// Every expected mechanism is active
+    Full,
+    /// Some mechanisms are active, with no record of what was applied
+    Partial,
+    /// Nothing is active and nothing was applied
+    None,
+    /// Something that was applied is no longer in place
+    Drifted,
+}
+
 /// Protection status result
 #[derive(serde::Serialize)]
 pub struct ProtectionStatus {
+    /// True if any mechanism is active (see `verdict` for the full picture)
     pub is_protected: bool,
     pub config_locked: bool,
     pub blockers_exist: bool,
+    pub hosts_blocked: bool,
+    pub verdict: ProtectionVerdict,
+    /// Strategy ids recorded by the last protection run
+    pub applied: Vec<String>,
+    /// When protection was last applied (Unix timestamp)
+    pub applied_at: Option<u64>,
+    /// Where ProductInfo.xml points the launcher (None while the blocker is in place)
+    pub launcher: Option<LauncherTarget>,
+    /// Status of every known strategy
+    pub strategies: Vec<StrategyStatus>,
+}
+
+/// Strategies applied when no record exists (the classic protection set)
+const DEFAULT_EXPECTED: &[&str] = &["config_lock", "blocker_files"];
+
+/// Compare observed strategy states against what was last applied
+fn compute_verdict(strategies: &[StrategyStatus], applied: &[String]) -> ProtectionVerdict {
+    let is_active = |id: &str| strategies.iter().any(|s| s.id == id && s.active);
+
+    if applied.is_empty() {
+        let active = DEFAULT_EXPECTED.iter().filter(|id| is_active(id)).count();
+        return if active == DEFAULT_EXPECTED.len() {
+            ProtectionVerdict::Full
+        } else if active > 0 || strategies.iter().any(|s| s.active) {
+            ProtectionVerdict::Partial
+        } else {
+            ProtectionVerdict::None
+        };
+    }
+
+    if applied.iter().all(|id| is_active(id)) {
+        ProtectionVerdict::Full
+    } else {
+        ProtectionVerdict::Drifted
+    }
 }
 
 /// Check if protection is currently applied
 #[tauri::command]
 pub fn check_protection_status() -> ProtectionStatus {
-    let capcut_paths = match paths::resolve_capcut_paths() {
-        Some(p) => p,
-        None => {
+    let manifest = ProtectionManifest::open_default().unwrap_or_default();
+    let applied: Vec<String> = manifest.applied.iter().map(|s| s.id.clone()).collect();
+
+    let ctx = match strategy_context() {
+        Ok(c) => c,
+        Err(_) => {
             return ProtectionStatus {
                 is_protected: false,
                 config_locked: false,
                 blockers_exist: false,
+                hosts_blocked: false,
+                verdict: if applied.is_empty() {
+                    ProtectionVerdict::None
+                } else {
+                    ProtectionVerdict::Drifted
+                },
+                applied,
+                applied_at: manifest.applied_at,
+                launcher: None,
+                strategies: vec![],
             }
         }
     };
 
-    let apps_path = capcut_paths.apps;
-    let capcut_root = capcut_paths.root;
-
-    // Check if ProductInfo.xml is a readonly empty file (lock)
-    let product_info = apps_path.join("ProductInfo.xml");
-    let blockers_exist = if product_info.exists() {
-        if let Ok(meta) = fs::metadata(&product_info) {
-            meta.len() == 0 && meta.permissions().readonly()
-        } else {
-            false
-        }
-    } else {
-        false
-    };
-
-    // Check if update.exe lock exists
-    let update_blocker = capcut_root
-        .join("User Data")
-        .join("Download")
-        .join("update.exe");
-    let update_blocked = if update_blocker.exists() {
-        if let Ok(meta) = fs::metadata(&update_blocker) {
-            meta.len() == 0 && meta.permissions().readonly()
-        } else {
-            false
-        }
-    } else {
-        false
-    };
-
-    // Check if configure.ini has last_version=1.0.0.0
-    let config_path = apps_path.join("configure.ini");
-    let config_locked = if config_path.exists() {
-        if let Ok(content) = fs::read_to_string(&config_path) {
-            content.contains("last_version=1.0.0.0")
-        } else {
-            false
-        }
-    } else {
-        false
-    };
+    // Verify each strategy with the options it was applied with
+    let strategies: Vec<StrategyStatus> = strategy::all_strategies()
+        .iter()
+        .map(|s| s.verify(&ctx, &manifest.applied_options(s.id())))
+        .collect();
+    let is_active = |id: &str| strategies.iter().any(|s| s.id == id && s.active);
 
     ProtectionStatus {
-        is_protected: blockers_exist || update_blocked || config_locked,
-        config_locked,
-        blockers_exist: blockers_exist || update_blocked,
+        is_protected: strategies.iter().any(|s| s.active),
+        config_locked: is_active("config_lock"),
+        blockers_exist: is_active("blocker_files"),
+        hosts_blocked: is_active("hosts_block"),
+        verdict: compute_verdict(&strategies, &applied),
+        applied,
+        applied_at: manifest.applied_at,
+        launcher: productinfo::launcher_target(&ctx.apps_path),
+        strategies,
     }
 }
 
 /// Remove all protection measures
 #[tauri::command]
 pub fn remove_protection() -> ProtectionResult {
-    let capcut_paths = match paths::resolve_capcut_paths() {
-        Some(p) => p,
-        None => {
-            return ProtectionResult {
-                success: false,
-                error: Some("Could not find CapCut installation".to_string()),
-                logs: vec![],
-            }
-        }
-    };
+    let _paused = watcher::pause();
+    let mut logs: Vec<String> = Vec::new();
+    let result = remove_protection_with(&mut FsOps::execute(), &mut logs);
 
-    let apps_path = capcut_paths.apps;
-    let capcut_root = capcut_paths.root;
+    ProtectionResult {
+        success: result.is_ok(),
+        error: result.err(),
+        logs,
+    }
+}
+
+/// Plan protection removal without touching disk
+#[tauri::command]
+pub fn plan_remove_protection() -> ChangePlan {
+    let mut ops = FsOps::plan();
     let mut logs: Vec<String> = Vec::new();
+    let result = remove_protection_with(&mut ops, &mut logs);
+    ops.into_plan(result, logs)
+}
+
+/// Revert every strategy, restoring manifest snapshots where recorded
+fn remove_protection_with(ops: &mut FsOps, logs: &mut Vec<String>) -> Result<(), String> {
+    let ctx = strategy_context()?;
+    let mut manifest = ProtectionManifest::open_default()?;
 
-    // Remove ProductInfo.xml lock
-    let product_info = apps_path.join("ProductInfo.xml");
-    if product_info.exists() {
-        logs.push("Removing ProductInfo.xml lock...".to_string());
-        if let Err(e) = unset_readonly_recursive(&product_info) {
-            logs.push(format!("[!] Warning: {}", e));
+    if manifest.is_empty() {
+        // Protection applied by an older version - best-effort cleanup
+        logs.push("[!] No protection manifest found, using legacy removal".to_string());
+    } else {
+        logs.push(format!(
+            "Restoring {} file(s) from protection manifest...",
+            manifest.entries.len()
+        ));
+    }
+
+    // Revert in reverse application order; without a record of what was applied,
+    // only the mechanisms older versions used are cleaned up
+    let legacy = manifest.applied.is_empty();
+    let mut failures = Vec::new();
+    for strategy in strategy::all_strategies().iter().rev() {
+        let applied = manifest.applied.iter().any(|s| s.id == strategy.id());
+        if !(applied || legacy && strategy.legacy()) {
+            continue;
         }
-        if let Err(e) = fs::remove_file(&product_info) {
-            logs.push(format!("[!] Could not remove ProductInfo.xml: {}", e));
-        } else {
-            logs.push("[OK] ProductInfo.xml lock removed".to_string());
+        let options = manifest.applied_options(strategy.id());
+        if let Err(e) = strategy.revert(&ctx, &options, &mut manifest, ops, logs) {
+            failures.push(format!("{}: {}", strategy.name(), e));
         }
     }
 
-    // Remove update.exe lock
-    let update_blocker = capcut_root
-        .join("User Data")
-        .join("Download")
-        .join("update.exe");
-    if update_blocker.exists() {
-        logs.push("Removing update.exe lock...".to_string());
-        if let Err(e) = unset_readonly_recursive(&update_blocker) {
-            logs.push(format!("[!] Warning: {}", e));
-        }
-        if let Err(e) = fs::remove_file(&update_blocker) {
-            logs.push(format!("[!] Could not remove update.exe: {}", e));
+    // Anything not claimed by a strategy is still restored from its snapshot
+    if !manifest.entries.is_empty() {
+        if let Err(e) = manifest.restore(ops, logs) {
+            failures.push(e);
         } else {
-            logs.push("[OK] update.exe lock removed".to_string());
+            manifest.entries.clear();
         }
     }
 
-    // Reset configure.ini (remove last_version lock)
-    let config_path = apps_path.join("configure.ini");
-    if config_path.exists() {
-        logs.push("Resetting configure.ini...".to_string());
-        if let Ok(content) = fs::read_to_string(&config_path) {
-            let new_content: String = content
-                .lines()
-                .filter(|line| !line.trim().starts_with("last_version"))
-                .collect::<Vec<_>>()
-                .join("\n");
-            if let Err(e) = fs::write(&config_path, new_content) {
-                logs.push(format!("[!] Could not reset configure.ini: {}", e));
-            } else {
-                logs.push("[OK] configure.ini reset".to_string());
-            }
+    if failures.is_empty() {
+        manifest.applied.clear();
+        manifest.applied_at = None;
+    }
+
+    if !ops.is_dry_run() {
+        let manifest_result = if manifest.is_empty() {
+            manifest.clear()
+        } else {
+            manifest.save()
+        };
+        if let Err(e) = manifest_result {
+            logs.push(format!("[!] Could not update protection manifest: {}", e));
         }
     }
 
+    if !failures.is_empty() {
+        return Err(format!("{} - manifest kept for retry", failures.join("; ")));
+    }
+
     logs.push("[OK] Protection removed - CapCut allows updates".to_string());
+    Ok(())
+}
 
-    ProtectionResult {
-        success: true,
-        error: None,
-        logs,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::strategy::MechanismState;
+    use crate::commands::test_support::{scratch_install, scratch_local_app_data};
+
+    fn status(id: &str, state: MechanismState) -> StrategyStatus {
+        StrategyStatus {
+            id: id.to_string(),
+            name: id.to_string(),
+            active: state == MechanismState::Active,
+            state,
+            details: vec![],
+            evidence: vec![],
+        }
+    }
+
+    fn observed(active: &[&str]) -> Vec<StrategyStatus> {
+        ["config_lock", "blocker_files", "hosts_block"]
+            .into_iter()
+            .map(|id| {
+                let state = if active.contains(&id) {
+                    MechanismState::Active
+                } else {
+                    MechanismState::Missing
+                };
+                status(id, state)
+            })
+            .collect()
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    fn states(status: &ProtectionStatus) -> Vec<(&str, MechanismState)> {
+        status
+            .strategies
+            .iter()
+            .map(|s| (s.id.as_str(), s.state))
+            .collect()
+    }
+
+    #[test]
+    fn without_a_record_the_classic_set_is_expected() {
+        use ProtectionVerdict::*;
+        assert_eq!(compute_verdict(&observed(&[]), &[]), None);
+        assert_eq!(
+            compute_verdict(&observed(&["config_lock", "blocker_files"]), &[]),
+            Full
+        );
+        assert_eq!(compute_verdict(&observed(&["config_lock"]), &[]), Partial);
+        assert_eq!(compute_verdict(&observed(&["hosts_block"]), &[]), Partial);
+    }
+
+    #[test]
+    fn recorded_strategies_must_all_be_active() {
+        use ProtectionVerdict::*;
+        let applied = ids(&["config_lock", "blocker_files"]);
+        assert_eq!(
+            compute_verdict(&observed(&["config_lock", "blocker_files"]), &applied),
+            Full
+        );
+        let mut tampered = observed(&["config_lock"]);
+        tampered[1] = status("blocker_files", MechanismState::Modified);
+        assert_eq!(compute_verdict(&tampered, &applied), Drifted);
+        assert_eq!(compute_verdict(&observed(&[]), &applied), Drifted);
+
+        // Only what was applied counts, not the classic set
+        let hosts_only = ids(&["hosts_block"]);
+        assert_eq!(
+            compute_verdict(&observed(&["hosts_block"]), &hosts_only),
+            Full
+        );
+    }
+
+    #[test]
+    fn status_follows_apply_tamper_and_lost_records() {
+        let (_guard, dir) = scratch_local_app_data("protection-status");
+        let apps = scratch_install(&dir, &["5.0.0.100"], "5.0.0.100");
+
+        let before = check_protection_status();
+        assert_eq!(before.verdict, ProtectionVerdict::None);
+        assert!(!before.is_protected);
+        assert_eq!(
+            states(&before),
+            [
+                // Pinned to the active version, not the lock value
+                ("config_lock", MechanismState::Modified),
+                // A real ProductInfo.xml where the blocker would be
+                ("blocker_files", MechanismState::UnexpectedContent),
+                ("hosts_block", MechanismState::Missing),
+            ]
+        );
+        let launcher = before.launcher.unwrap();
+        assert_eq!(launcher.version.as_deref(), Some("5.0.0.100"));
+
+        let specs = [
+            StrategySpec::new("config_lock"),
+            StrategySpec::new("blocker_files"),
+        ];
+        assert!(apply_strategies(&specs).success);
+        let protected = check_protection_status();
+        assert_eq!(protected.verdict, ProtectionVerdict::Full);
+        assert_eq!(protected.applied, ids(&["config_lock", "blocker_files"]));
+        assert!(protected.config_locked && protected.blockers_exist && !protected.hosts_blocked);
+        assert!(protected.applied_at.is_some());
+        assert!(protected.launcher.is_none());
+
+        // A blocker that is no longer read-only is drift
+        let product_info = apps.join("ProductInfo.xml");
+        FsOps::execute().set_readonly(&product_info, false).unwrap();
+        let drifted = check_protection_status();
+        assert_eq!(drifted.verdict, ProtectionVerdict::Drifted);
+        assert_eq!(
+            states(&drifted)[..2],
+            [
+                ("config_lock", MechanismState::Active),
+                ("blocker_files", MechanismState::Modified),
+            ]
+        );
+
+        // Protection from releases that kept no record is judged against the classic set
+        FsOps::execute().mark_readonly(&product_info).unwrap();
+        ProtectionManifest::open_default().unwrap().clear().unwrap();
+        let legacy = check_protection_status();
+        assert!(legacy.applied.is_empty());
+        assert_eq!(legacy.verdict, ProtectionVerdict::Full);
     }
 }
@@ -0,0 +1,313 @@
+//! Version recommendations
+//! Ranks catalog versions against user requirements and explains every match or miss
+
+use serde::{Deserialize, Serialize};
+
+use super::catalog::{self, Access, Catalog};
+use super::catalog_query::{self, CatalogItem, Entry};
+use super::catalog_store;
+use super::version::CapCutVersion;
+
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 200;
+
+/// What the user needs from a version
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Requirements {
+    /// Feature ids that must be present (free or paid)
+    pub features: Vec<String>,
+    /// Feature ids that must not be behind the paywall
+    pub no_paywall: Vec<String>,
+    /// Highest acceptable risk level
+    pub max_risk: Option<String>,
+    /// Must open projects created in this version
+    pub opens_projects_from: Option<String>,
+    /// Consider betas and test builds
+    pub include_prerelease: bool,
+    /// Consider versions newer than CC Version Guard supports
+    pub include_incompatible: bool,
+    pub limit: Option<usize>,
+}
+
+/// Outcome of checking one requirement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Met,
+    Failed,
+    /// Not disqualifying, but worth knowing (e.g. no data for a feature)
+    Warning,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Reason {
+    pub outcome: Outcome,
+    pub message: String,
+}
+
+/// A ranked catalog version
+#[derive(Debug, Clone, Serialize)]
+pub struct Recommendation {
+    #[serde(flatten)]
+    pub item: CatalogItem,
+    /// Every requirement is met
+    pub matched: bool,
+    /// Requirements not met
+    pub failures: usize,
+    /// Higher is better; only comparable between matched versions
+    pub score: i32,
+    pub reasons: Vec<Reason>,
+    /// Curated persona built on this release (`persona` is the archive entry's own label)
+    pub curated_persona: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecommendationResult {
+    pub recommendations: Vec<Recommendation>,
+    /// Versions meeting every requirement
+    pub matched: usize,
+    pub evaluated: usize,
+}
+
+fn release(v: &CapCutVersion) -> (u32, u32, u32) {
+    (v.major, v.minor, v.patch)
+}
+
+/// Reject requirements that name unknown features, risk levels or versions
+fn validate(requirements: &Requirements, catalog: &Catalog) -> Result<(), String> {
+    for id in requirements.features.iter().chain(&requirements.no_paywall) {
+        if !catalog.features.iter().any(|f| &f.id == id) {
+            return Err(format!("Unknown feature: {}", id));
+        }
+    }
+    if let Some(risk) = &requirements.max_risk {
+        catalog::risk_rank(risk).ok_or_else(|| format!("Unknown risk level: {}", risk))?;
+    }
+    if let Some(version) = &requirements.opens_projects_from {
+        CapCutVersion::parse(version).ok_or_else(|| format!("{} is not a version", version))?;
+    }
+    Ok(())
+}
+
+struct Evaluation {
+    reasons: Vec<Reason>,
+    score: i32,
+}
+
+impl Evaluation {
+    fn push(&mut self, outcome: Outcome, message: String) {
+        self.reasons.push(Reason { outcome, message });
+    }
+
+    fn failures(&self) -> usize {
+        self.reasons
+            .iter()
+            .filter(|r| r.outcome == Outcome::Failed)
+            .count()
+    }
+}
+
+fn evaluate(entry: &Entry, requirements: &Requirements, catalog: &Catalog) -> Evaluation {
+    let item = &entry.item;
+    let mut eval = Evaluation {
+        reasons: Vec::new(),
+        score: 100,
+    };
+
+    for id in &requirements.features {
+        let Some(feature) = catalog.features.iter().find(|f| &f.id == id) else {
+            continue;
+        };
+        match feature.access(&entry.version) {
+            Some(Access::Free) | Some(Access::Paid) => {
+                eval.push(Outcome::Met, format!("Has {}", feature.name))
+            }
+            Some(Access::Unavailable) => eval.push(
+                Outcome::Failed,
+                format!("{} is not available", feature.name),
+            ),
+            None => {
+                eval.score -= 5;
+                eval.push(
+                    Outcome::Warning,
+                    format!("No data on {} for this release", feature.name),
+                );
+            }
+        }
+    }
+
+    for id in &requirements.no_paywall {
+        let Some(feature) = catalog.features.iter().find(|f| &f.id == id) else {
+            continue;
+        };
+        match feature.access(&entry.version) {
+            Some(Access::Free) => eval.push(Outcome::Met, format!("{} is free", feature.name)),
+            Some(Access::Paid) => eval.push(
+                Outcome::Failed,
+                format!("{} is behind the paywall", feature.name),
+            ),
+            Some(Access::Unavailable) => eval.push(
+                Outcome::Failed,
+                format!("{} is not available", feature.name),
+            ),
+            None => {
+                eval.score -= 5;
+                eval.push(
+                    Outcome::Warning,
+                    format!("No data on whether {} is free", feature.name),
+                );
+            }
+        }
+    }
+
+    let risk = catalog::risk_rank(&item.archive.risk_level).unwrap_or(0);
+    eval.score -= 15 * risk as i32;
+    if let Some(max) = requirements
+        .max_risk
+        .as_deref()
+        .and_then(catalog::risk_rank)
+    {
+        let outcome = if risk <= max {
+            Outcome::Met
+        } else {
+            Outcome::Failed
+        };
+        eval.push(outcome, format!("{} risk", item.archive.risk_level));
+    }
+
+    if let Some(origin) = requirements
+        .opens_projects_from
+        .as_deref()
+        .and_then(CapCutVersion::parse)
+    {
+        // Projects only open in the release that created them or a newer one
+        if release(&entry.version) >= release(&origin) {
+            eval.push(
+                Outcome::Met,
+                format!("Opens projects from {}", origin.base()),
+            );
+        } else {
+            eval.push(
+                Outcome::Failed,
+                format!("Too old to open projects from {}", origin.base()),
+            );
+        }
+    }
+
+    if entry.version.channel.is_prerelease() {
+        eval.score -= 10;
+        if requirements.include_prerelease {
+            eval.push(Outcome::Warning, format!("{} build", entry.version.channel));
+        } else {
+            eval.push(
+                Outcome::Failed,
+                format!(
+                    "{} build - pre-releases not included",
+                    entry.version.channel
+                ),
+            );
+        }
+    }
+
+    if !item.compatible {
+        let outcome = if requirements.include_incompatible {
+            Outcome::Warning
+        } else {
+            Outcome::Failed
+        };
+        eval.push(
+            outcome,
+            "Newer than CC Version Guard supports - protection may not hold".to_string(),
+        );
+    }
+
+    eval
+}
+
+/// Rank catalog versions against the user's requirements
+#[tauri::command]
+pub fn recommend_versions(requirements: Requirements) -> Result<RecommendationResult, String> {
+    let catalog = catalog_store::current();
+    validate(&requirements, &catalog)?;
+
+    let entries = catalog_query::load_entries(&catalog);
+    let mut recommendations: Vec<(CapCutVersion, Recommendation)> = entries
+        .into_iter()
+        .map(|entry| {
+            let mut eval = evaluate(&entry, &requirements, &catalog);
+            let curated_persona = catalog
+                .personas
+                .iter()
+                .filter_map(|p| Some((CapCutVersion::parse(&p.version)?, p)))
+                .find(|(v, _)| release(v) == release(&entry.version))
+                .map(|(_, p)| p.name.clone());
+            if let Some(name) = &curated_persona {
+                eval.score += 10;
+                eval.push(Outcome::Met, format!("Curated pick: {}", name));
+            }
+            let recommendation = Recommendation {
+                matched: eval.failures() == 0,
+                failures: eval.failures(),
+                score: eval.score,
+                reasons: eval.reasons,
+                item: entry.item,
+                curated_persona,
+            };
+            (entry.version, recommendation)
+        })
+        .collect();
+
+    // Fewest failed requirements first, then best score, then newest
+    recommendations.sort_by(|(va, a), (vb, b)| {
+        a.failures
+            .cmp(&b.failures)
+            .then(b.score.cmp(&a.score))
+            .then(vb.cmp(va))
+    });
+
+    let evaluated = recommendations.len();
+    let matched = recommendations.iter().filter(|(_, r)| r.matched).count();
+    let limit = requirements
+        .limit
+        .unwrap_or(DEFAULT_LIMIT)
+        .clamp(1, MAX_LIMIT);
+    Ok(RecommendationResult {
+        recommendations: recommendations
+            .into_iter()
+            .take(limit)
+            .map(|(_, r)| r)
+            .collect(),
+        matched,
+        evaluated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{json_keys, scratch_local_app_data};
+
+    #[test]
+    fn curated_persona_does_not_clash_with_the_archive_persona() {
+        let (_guard, _) = scratch_local_app_data("recommend-keys");
+        let result = recommend_versions(Requirements {
+            include_prerelease: true,
+            include_incompatible: true,
+            limit: Some(MAX_LIMIT),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(result
+            .recommendations
+            .iter()
+            .any(|r| r.curated_persona.is_some()));
+
+        for recommendation in &result.recommendations {
+            let keys = json_keys(recommendation);
+            for key in ["persona", "curated_persona"] {
+                assert_eq!(keys.iter().filter(|k| *k == key).count(), 1, "{:?}", keys);
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! Rule-based risk assessment
+//! Sums the catalog's risk rules that apply to a version and maps the total onto a risk level
+
+use serde::{Deserialize, Serialize};
+
+use super::catalog::{Catalog, RISK_LEVELS};
+use super::version::CapCutVersion;
+
+/// A risk rule that applied to a version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskReason {
+    /// Rule id ("curated" for a persona's own rating)
+    pub id: String,
+    pub points: u32,
+    pub reason: String,
+}
+
+/// Risk of one version with everything that contributed to it
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskAssessment {
+    pub level: String,
+    pub score: u32,
+    pub reasons: Vec<RiskReason>,
+}
+
+/// Apply the catalog's risk rules to a version
+pub fn assess(catalog: &Catalog, version: &CapCutVersion) -> RiskAssessment {
+    let compatible = catalog.is_compatible(version);
+    let reasons: Vec<RiskReason> = catalog
+        .risk_rules
+        .iter()
+        .filter(|rule| rule.when.matches(version, compatible, &catalog.features))
+        .map(|rule| RiskReason {
+            id: rule.id.clone(),
+            points: rule.points,
+            reason: rule.reason.clone(),
+        })
+        .collect();
+    let score = reasons.iter().map(|r| r.points).sum();
+
+    // Thresholds are validated to ascend, so the last one reached wins
+    let level = catalog
+        .risk_thresholds
+        .iter()
+        .rev()
+        .find(|t| score >= t.points)
+        .map(|t| t.level.clone())
+        .unwrap_or_else(|| RISK_LEVELS[0].to_string());
+
+    RiskAssessment {
+        level,
+        score,
+        reasons,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const INSTALLERS: &str = "https://example.com/packages";
+
+    fn fixture() -> Catalog {
+        let catalog = json!({
+            "schema_version": 1,
+            "catalog_version": 1,
+            "last_compatible": "5.4.0 (Beta6)",
+            "personas": [],
+            "features": [{
+                "id": "auto_captions",
+                "name": "Auto-captions",
+                "category": "AI",
+                "availability": [
+                    { "until": "3.9.0", "access": "free" },
+                    { "from": "4.0.0", "access": "paid" }
+                ]
+            }],
+            "risk_rules": [
+                { "id": "beta_build", "when": { "channel": ["beta"] }, "points": 2, "reason": "Beta build" },
+                { "id": "captions_paywalled", "when": { "paywalled": ["auto_captions"] }, "points": 1, "reason": "Captions are paid" },
+                { "id": "forced_updates", "when": { "from": "4.0.0" }, "points": 2, "reason": "Forces updates" },
+                { "id": "unsupported", "when": { "compatible": false }, "points": 4, "reason": "Unsupported" }
+            ],
+            "risk_thresholds": [
+                { "level": "Medium", "points": 2 },
+                { "level": "High", "points": 4 }
+            ],
+            "versions": [
+                {
+                    "label": "5.4.0 (Beta6)",
+                    "url": format!("{}/CapCut_5_4_0_1991_beta6_capcutpc_beta_creatortool.exe", INSTALLERS)
+                },
+                {
+                    "label": "3.9.0",
+                    "url": format!("{}/CapCut_3_9_0_1459_capcutpc_0_creatortool.exe", INSTALLERS)
+                }
+            ]
+        });
+        Catalog::parse(&catalog.to_string()).unwrap()
+    }
+
+    fn assess_label(catalog: &Catalog, label: &str) -> (String, u32, Vec<String>) {
+        let risk = assess(catalog, &CapCutVersion::parse(label).unwrap());
+        let ids = risk.reasons.into_iter().map(|r| r.id).collect();
+        (risk.level, risk.score, ids)
+    }
+
+    #[test]
+    fn no_matching_rule_is_low_risk() {
+        let catalog = fixture();
+        assert_eq!(assess_label(&catalog, "3.9.0"), ("Low".into(), 0, vec![]));
+    }
+
+    #[test]
+    fn paywall_changes_and_forced_updates_add_up() {
+        let catalog = fixture();
+        assert_eq!(
+            assess_label(&catalog, "4.0.0"),
+            (
+                "Medium".into(),
+                3,
+                vec!["captions_paywalled".into(), "forced_updates".into()]
+            )
+        );
+    }
+
+    #[test]
+    fn channel_rules_apply_to_betas() {
+        let catalog = fixture();
+        let (level, score, reasons) = assess_label(&catalog, "5.4.0 (Beta6)");
+        assert_eq!((level.as_str(), score), ("High", 5));
+        assert_eq!(
+            reasons,
+            ["beta_build", "captions_paywalled", "forced_updates"]
+        );
+    }
+
+    #[test]
+    fn newer_than_last_compatible_is_unsupported() {
+        let catalog = fixture();
+        let (level, score, reasons) = assess_label(&catalog, "5.5.0");
+        assert_eq!((level.as_str(), score), ("High", 7));
+        assert!(reasons.contains(&"unsupported".to_string()));
+        // The stable 5.4.0 release came after Beta6
+        assert!(!catalog.is_compatible(&CapCutVersion::parse("5.4.0").unwrap()));
+    }
+
+    #[test]
+    fn builds_of_the_last_compatible_release_stay_compatible() {
+        let catalog = fixture();
+        for version in [
+            "5.4.0 (Beta6)",
+            "5.4.0.1991 (Beta6)",
+            // A folder name or PE file version names no channel
+            "5.4.0.1991",
+            "5.4.0 (Beta5)",
+            "5.3.0.1964",
+        ] {
+            let parsed = CapCutVersion::parse(version).unwrap();
+            assert!(catalog.is_compatible(&parsed), "{}", version);
+            let (_, _, reasons) = assess_label(&catalog, version);
+            assert!(!reasons.contains(&"unsupported".to_string()), "{}", version);
+        }
+    }
+}
@@ -0,0 +1,798 @@
+//! Pluggable protection strategies
+//! Each anti-update mechanism implements ProtectionStrategy and is selected by id
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::fsops::FsOps;
+use super::ini::{self, IniDocument};
+use super::manifest::ProtectionManifest;
+use super::productinfo::{self, ProductInfo};
+
+/// Locations a strategy operates on
+pub struct StrategyContext {
+    /// Root CapCut directory
+    pub capcut_root: PathBuf,
+    /// Apps directory containing versions
+    pub apps_path: PathBuf,
+}
+
+/// A strategy selected for a protection run, with its options
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategySpec {
+    pub id: String,
+    #[serde(default)]
+    pub options: Value,
+}
+
+impl StrategySpec {
+    pub fn new(id: &str) -> Self {
+        StrategySpec {
+            id: id.to_string(),
+            options: Value::Null,
+        }
+    }
+}
+
+/// Observed state of a protection mechanism
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MechanismState {
+    /// Fully in place
+    Active,
+    /// Not present at all
+    Missing,
+    /// Present but altered (e.g. read-only flag cleared, different pinned value)
+    Modified,
+    /// Replaced with content protection never writes
+    UnexpectedContent,
+}
+
+/// What was observed on disk for a single file
+#[derive(Debug, Clone, Serialize)]
+pub struct FileEvidence {
+    pub path: String,
+    pub exists: bool,
+    pub size: Option<u64>,
+    pub readonly: Option<bool>,
+    /// Relevant value read from the file (e.g. the actual last_version)
+    pub value: Option<String>,
+}
+
+impl FileEvidence {
+    /// Collect size and read-only flag for a path
+    pub fn observe(path: &Path) -> Self {
+        let meta = fs::metadata(path).ok();
+        FileEvidence {
+            path: path.to_string_lossy().to_string(),
+            exists: meta.is_some(),
+            size: meta.as_ref().map(|m| m.len()),
+            readonly: meta.as_ref().map(|m| m.permissions().readonly()),
+            value: None,
+        }
+    }
+}
+
+/// Observed state of a single strategy
+#[derive(Debug, Clone, Serialize)]
+pub struct StrategyStatus {
+    pub id: String,
+    pub name: String,
+    pub active: bool,
+    pub state: MechanismState,
+    pub details: Vec<String>,
+    pub evidence: Vec<FileEvidence>,
+}
+
+impl StrategyStatus {
+    fn new(
+        strategy: &dyn ProtectionStrategy,
+        state: MechanismState,
+        details: Vec<String>,
+        evidence: Vec<FileEvidence>,
+    ) -> Self {
+        StrategyStatus {
+            id: strategy.id().to_string(),
+            name: strategy.name().to_string(),
+            active: state == MechanismState::Active,
+            state,
+            details,
+            evidence,
+        }
+    }
+}
+
+/// An anti-update mechanism that can be applied, verified and reverted
+pub trait ProtectionStrategy {
+    /// Stable identifier used in ProtectionParams
+    fn id(&self) -> &'static str;
+
+    /// Human-readable name for logs and status
+    fn name(&self) -> &'static str;
+
+    /// Files this strategy modifies (snapshotted before apply)
+    fn touched_paths(&self, ctx: &StrategyContext, options: &Value) -> Vec<PathBuf>;
+
+    /// Apply the mechanism, recording originals in the manifest
+    fn apply(
+        &self,
+        ctx: &StrategyContext,
+        options: &Value,
+        manifest: &mut ProtectionManifest,
+        ops: &mut FsOps,
+    ) -> Result<(), String>;
+
+    /// Check whether the mechanism is currently in place
+    fn verify(&self, ctx: &StrategyContext, options: &Value) -> StrategyStatus;
+
+    /// Whether Guard versions that did not record applied strategies used this mechanism
+    fn legacy(&self) -> bool {
+        false
+    }
+
+    /// Undo the mechanism, restoring recorded originals where available
+    fn revert(
+        &self,
+        ctx: &StrategyContext,
+        options: &Value,
+        manifest: &mut ProtectionManifest,
+        ops: &mut FsOps,
+        logs: &mut Vec<String>,
+    ) -> Result<(), String>;
+}
+
+/// All known strategies, in default application order
+pub fn all_strategies() -> Vec<Box<dyn ProtectionStrategy>> {
+    vec![
+        Box::new(ConfigLockStrategy),
+        Box::new(BlockerFilesStrategy),
+        Box::new(HostsBlockStrategy),
+    ]
+}
+
+/// Look up a strategy by id
+pub fn strategy_by_id(id: &str) -> Option<Box<dyn ProtectionStrategy>> {
+    all_strategies().into_iter().find(|s| s.id() == id)
+}
+
+/// Read a boolean option, falling back to a default
+pub fn opt_bool(options: &Value, key: &str, default: bool) -> bool {
+    options.get(key).and_then(Value::as_bool).unwrap_or(default)
+}
+
+/// Read a string option, falling back to a default
+pub fn opt_str<'a>(options: &'a Value, key: &str, default: &'a str) -> &'a str {
+    options.get(key).and_then(Value::as_str).unwrap_or(default)
+}
+
+/// Restore a strategy's files from the manifest.
+/// Returns false if the manifest holds no snapshot for them (legacy protection).
+fn revert_from_manifest(
+    paths: &[PathBuf],
+    manifest: &mut ProtectionManifest,
+    ops: &mut FsOps,
+    logs: &mut Vec<String>,
+) -> Result<bool, String> {
+    let keys: Vec<String> = paths
+        .iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+    if !manifest.entries.iter().any(|e| keys.contains(&e.path)) {
+        return Ok(false);
+    }
+    manifest.restore_where(|e| keys.contains(&e.path), ops, logs)?;
+    manifest.entries.retain(|e| !keys.contains(&e.path));
+    Ok(true)
+}
+
+/// Read an INI file as it stands after the changes `ops` has planned so far,
+/// or start an empty document if it does not exist
+pub fn load_ini(path: &Path, ops: &FsOps) -> Result<IniDocument, String> {
+    if !ops.exists(path) {
+        return Ok(IniDocument::new());
+    }
+    let bytes = ops
+        .read(path)
+        .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    IniDocument::parse_bytes(&bytes)
+        .map_err(|e| format!("Could not parse {}: {}", path.display(), e))
+}
+
+/// Create readonly lock file, snapshotting whatever was there first
+fn create_readonly(
+    path: &Path,
+    manifest: &mut ProtectionManifest,
+    ops: &mut FsOps,
+) -> Result<(), String> {
+    manifest.record(path, ops)?;
+    ops.remove(path)?;
+    ops.write(path, "")?;
+    ops.mark_readonly(path)
+}
+
+/// Pins `last_version` in configure.ini so the launcher never sees a newer version
+pub struct ConfigLockStrategy;
+
+/// Value written to `last_version` unless overridden with the `version` option
+pub const LOCKED_VERSION: &str = "1.0.0.0";
+
+impl ProtectionStrategy for ConfigLockStrategy {
+    fn id(&self) -> &'static str {
+        "config_lock"
+    }
+
+    fn name(&self) -> &'static str {
+        "Configuration lock"
+    }
+
+    fn touched_paths(&self, ctx: &StrategyContext, _options: &Value) -> Vec<PathBuf> {
+        vec![ctx.apps_path.join("configure.ini")]
+    }
+
+    fn apply(
+        &self,
+        ctx: &StrategyContext,
+        options: &Value,
+        manifest: &mut ProtectionManifest,
+        ops: &mut FsOps,
+    ) -> Result<(), String> {
+        let config_path = ctx.apps_path.join("configure.ini");
+        manifest.record(&config_path, ops)?;
+
+        let mut config = load_ini(&config_path, ops)?;
+        // Older lock files put the key before any section header
+        config.delete("", "last_version");
+        config.set(
+            ini::CONFIGURE_SECTION,
+            "last_version",
+            opt_str(options, "version", LOCKED_VERSION),
+        );
+        ops.write(&config_path, config.to_bytes())
+    }
+
+    fn verify(&self, ctx: &StrategyContext, options: &Value) -> StrategyStatus {
+        let config_path = ctx.apps_path.join("configure.ini");
+        let expected = opt_str(options, "version", LOCKED_VERSION);
+        let mut evidence = FileEvidence::observe(&config_path);
+
+        let (state, detail) = if !evidence.exists {
+            (
+                MechanismState::Missing,
+                "configure.ini does not exist".to_string(),
+            )
+        } else {
+            match IniDocument::load(&config_path) {
+                Err(_) => (
+                    MechanismState::UnexpectedContent,
+                    "configure.ini is not readable text".to_string(),
+                ),
+                Ok(config) => {
+                    let actual = config
+                        .get(ini::CONFIGURE_SECTION, "last_version")
+                        .or_else(|| config.get("", "last_version"))
+                        .map(str::to_string);
+                    evidence.value = actual.clone();
+                    match actual {
+                        None => (
+                            MechanismState::Missing,
+                            "configure.ini has no last_version".to_string(),
+                        ),
+                        Some(v) if v == expected => (
+                            MechanismState::Active,
+                            format!("last_version pinned to {}", v),
+                        ),
+                        Some(v) => (
+                            MechanismState::Modified,
+                            format!("last_version is {} (expected {})", v, expected),
+                        ),
+                    }
+                }
+            }
+        };
+
+        StrategyStatus::new(self, state, vec![detail], vec![evidence])
+    }
+
+    fn legacy(&self) -> bool {
+        true
+    }
+
+    fn revert(
+        &self,
+        ctx: &StrategyContext,
+        options: &Value,
+        manifest: &mut ProtectionManifest,
+        ops: &mut FsOps,
+        logs: &mut Vec<String>,
+    ) -> Result<(), String> {
+        if revert_from_manifest(&self.touched_paths(ctx, options), manifest, ops, logs)? {
+            return Ok(());
+        }
+
+        // Legacy: no snapshot, strip the last_version lock
+        let config_path = ctx.apps_path.join("configure.ini");
+        if ops.exists(&config_path) {
+            logs.push("Resetting configure.ini...".to_string());
+            if let Ok(mut config) = load_ini(&config_path, ops) {
+                let removed = config.delete(ini::CONFIGURE_SECTION, "last_version");
+                if config.delete("", "last_version") || removed {
+                    ops.write(&config_path, config.to_bytes())
+                        .map_err(|e| format!("Could not reset configure.ini: {}", e))?;
+                }
+                logs.push("[OK] configure.ini reset".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Replaces ProductInfo.xml and the downloaded update.exe with empty read-only files
+pub struct BlockerFilesStrategy;
+
+impl BlockerFilesStrategy {
+    /// Paths of the enabled blockers
+    fn blockers(&self, ctx: &StrategyContext, options: &Value) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        if opt_bool(options, "product_info", true) {
+            paths.push(productinfo::product_info_path(&ctx.apps_path));
+        }
+        if opt_bool(options, "update_exe", true) {
+            paths.push(
+                ctx.capcut_root
+                    .join("User Data")
+                    .join("Download")
+                    .join("update.exe"),
+            );
+        }
+        paths
+    }
+}
+
+impl ProtectionStrategy for BlockerFilesStrategy {
+    fn id(&self) -> &'static str {
+        "blocker_files"
+    }
+
+    fn name(&self) -> &'static str {
+        "Blocker files"
+    }
+
+    fn touched_paths(&self, ctx: &StrategyContext, options: &Value) -> Vec<PathBuf> {
+        self.blockers(ctx, options)
+    }
+
+    fn apply(
+        &self,
+        ctx: &StrategyContext,
+        options: &Value,
+        manifest: &mut ProtectionManifest,
+        ops: &mut FsOps,
+    ) -> Result<(), String> {
+        for path in self.blockers(ctx, options) {
+            if let Some(parent) = path.parent() {
+                ops.create_dir_all(parent)?;
+            }
+            create_readonly(&path, manifest, ops)?;
+        }
+        Ok(())
+    }
+
+    fn verify(&self, ctx: &StrategyContext, options: &Value) -> StrategyStatus {
+        let mut states = Vec::new();
+        let mut details = Vec::new();
+        let mut evidence = Vec::new();
+
+        for path in self.blockers(ctx, options) {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let mut observed = FileEvidence::observe(&path);
+            let (state, mut detail) = match (observed.exists, observed.size, observed.readonly) {
+                (false, _, _) => (MechanismState::Missing, format!("{} blocker missing", name)),
+                (true, Some(0), Some(true)) if path.is_file() => {
+                    (MechanismState::Active, format!("{} blocker in place", name))
+                }
+                (true, Some(0), _) if path.is_file() => (
+                    MechanismState::Modified,
+                    format!("{} blocker is no longer read-only", name),
+                ),
+                _ => (
+                    MechanismState::UnexpectedContent,
+                    format!("{} was replaced with real content", name),
+                ),
+            };
+            if state == MechanismState::UnexpectedContent {
+                // A real ProductInfo.xml means the launcher has a target again
+                if let Ok(info) = ProductInfo::load(&path) {
+                    observed.value = info.active_version();
+                    if let Some(v) = &observed.value {
+                        detail = format!("{} points the launcher at {}", name, v);
+                    }
+                }
+            }
+            states.push(state);
+            details.push(detail);
+            evidence.push(observed);
+        }
+
+        let state = if states.iter().all(|s| *s == MechanismState::Active) {
+            MechanismState::Active
+        } else if states.iter().all(|s| *s == MechanismState::Missing) {
+            MechanismState::Missing
+        } else if states.contains(&MechanismState::UnexpectedContent) {
+            MechanismState::UnexpectedContent
+        } else {
+            MechanismState::Modified
+        };
+
+        StrategyStatus::new(self, state, details, evidence)
+    }
+
+    fn legacy(&self) -> bool {
+        true
+    }
+
+    fn revert(
+        &self,
+        ctx: &StrategyContext,
+        options: &Value,
+        manifest: &mut ProtectionManifest,
+        ops: &mut FsOps,
+        logs: &mut Vec<String>,
+    ) -> Result<(), String> {
+        let paths = self.blockers(ctx, options);
+        if revert_from_manifest(&paths, manifest, ops, logs)? {
+            return Ok(());
+        }
+
+        // Legacy: no snapshot, just remove the blockers
+        for path in &paths {
+            if !ops.exists(path) {
+                continue;
+            }
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            logs.push(format!("Removing {} lock...", name));
+            match ops.remove(path) {
+                Ok(()) => logs.push(format!("[OK] {} lock removed", name)),
+                Err(e) => logs.push(format!("[!] Could not remove {}: {}", name, e)),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Named domain lists for the hosts strategy.
+/// Known endpoints only - extend as new update paths are observed.
+const HOSTS_DOMAIN_LISTS: &[(&str, &[&str])] = &[
+    (
+        "update",
+        &[
+            "update.capcut.com",
+            "updater.capcut.com",
+            "lf-capcut-update.faceulv.com",
+            "sf16-capcut-update.faceulv.com",
+        ],
+    ),
+    (
+        "telemetry",
+        &[
+            "log.capcut.com",
+            "log-va.capcut.com",
+            "mon.capcut.com",
+            "mcs.capcut.com",
+        ],
+    ),
+];
+
+const HOSTS_BLOCK_BEGIN: &str = "# >>> CC Version Guard managed block (do not edit) >>>";
+/// Begin marker used when the file did not end with a line break, so revert removes the one
+/// apply added before the block
+const HOSTS_BLOCK_BEGIN_JOINED: &str =
+    "# >>> CC Version Guard managed block (do not edit, line break added) >>>";
+const HOSTS_BLOCK_END: &str = "# <<< CC Version Guard managed block <<<";
+
+/// Blocks update and telemetry endpoints with a managed block in the hosts file
+pub struct HostsBlockStrategy;
+
+impl HostsBlockStrategy {
+    /// Hosts file location, overridable with the `hosts_path` option
+    fn hosts_path(&self, options: &Value) -> PathBuf {
+        if let Some(path) = options.get("hosts_path").and_then(Value::as_str) {
+            return PathBuf::from(path);
+        }
+        let system_root = std::env::var("SystemRoot").unwrap_or_else(|_| "C:\\Windows".into());
+        PathBuf::from(system_root)
+            .join("System32")
+            .join("drivers")
+            .join("etc")
+            .join("hosts")
+    }
+
+    /// Domains from the lists selected with the `lists` option (default: update)
+    fn domains(&self, options: &Value) -> Result<Vec<&'static str>, String> {
+        let lists: Vec<&str> = match options.get("lists").and_then(Value::as_array) {
+            Some(names) => names.iter().filter_map(Value::as_str).collect(),
+            None => vec!["update"],
+        };
+
+        let mut domains = Vec::new();
+        for list in lists {
+            let (_, entries) = HOSTS_DOMAIN_LISTS
+                .iter()
+                .find(|(name, _)| *name == list)
+                .ok_or_else(|| format!("Unknown domain list: {}", list))?;
+            for domain in entries.iter() {
+                if !domains.contains(domain) {
+                    domains.push(*domain);
+                }
+            }
+        }
+        Ok(domains)
+    }
+
+    /// Raw hosts file bytes; other software may have written any encoding,
+    /// so lines outside the managed block are passed through untouched
+    fn read_hosts(&self, path: &Path, ops: &FsOps) -> Result<Vec<u8>, String> {
+        if !ops.exists(path) {
+            return Ok(Vec::new());
+        }
+        ops.read(path)
+            .map_err(|e| format!("Could not read hosts file: {}", e))
+    }
+}
+
+/// Remove the managed block, returning the remaining content and the domains it listed.
+/// A block without its end marker is left alone, since there is no telling where it stops.
+fn strip_hosts_block(content: &[u8]) -> Result<(Vec<u8>, Vec<String>), String> {
+    let mut kept = Vec::with_capacity(content.len());
+    let mut blocked = Vec::new();
+    let mut inside = false;
+
+    for line in content.split_inclusive(|b| *b == b'\n') {
+        let trimmed = line.trim_ascii();
+        let joined = trimmed == HOSTS_BLOCK_BEGIN_JOINED.as_bytes();
+        if joined || trimmed == HOSTS_BLOCK_BEGIN.as_bytes() {
+            if inside {
+                return Err("Hosts file has a nested CC Version Guard block".to_string());
+            }
+            inside = true;
+            if joined {
+                let eol = if kept.ends_with(b"\r\n") { 2 } else { 1 };
+                if kept.ends_with(b"\n") {
+                    kept.truncate(kept.len() - eol);
+                }
+            }
+        } else if inside && trimmed == HOSTS_BLOCK_END.as_bytes() {
+            inside = false;
+        } else if inside {
+            if let Some(domain) = String::from_utf8_lossy(trimmed).split_whitespace().nth(1) {
+                blocked.push(domain.to_string());
+            }
+        } else {
+            kept.extend_from_slice(line);
+        }
+    }
+
+    if inside {
+        return Err(format!(
+            "Hosts file has a CC Version Guard block without its end marker \"{}\" - \
+             fix or remove the block by hand",
+            HOSTS_BLOCK_END
+        ));
+    }
+    Ok((kept, blocked))
+}
+
+impl ProtectionStrategy for HostsBlockStrategy {
+    fn id(&self) -> &'static str {
+        "hosts_block"
+    }
+
+    fn name(&self) -> &'static str {
+        "Hosts file block"
+    }
+
+    fn touched_paths(&self, _ctx: &StrategyContext, options: &Value) -> Vec<PathBuf> {
+        vec![self.hosts_path(options)]
+    }
+
+    fn apply(
+        &self,
+        _ctx: &StrategyContext,
+        options: &Value,
+        _manifest: &mut ProtectionManifest,
+        ops: &mut FsOps,
+    ) -> Result<(), String> {
+        // The hosts file is shared with other software, so it is never snapshotted:
+        // revert removes only our block instead of restoring the whole file
+        let path = self.hosts_path(options);
+        let domains = self.domains(options)?;
+        let content = self.read_hosts(&path, ops)?;
+        let eol = if content.windows(2).any(|w| w == b"\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+
+        let (mut updated, _) = strip_hosts_block(&content)?;
+        let begin = if !updated.is_empty() && !updated.ends_with(b"\n") {
+            updated.extend_from_slice(eol.as_bytes());
+            HOSTS_BLOCK_BEGIN_JOINED
+        } else {
+            HOSTS_BLOCK_BEGIN
+        };
+        let mut block = format!("{}{}", begin, eol);
+        for domain in &domains {
+            block.push_str(&format!("0.0.0.0 {}{}", domain, eol));
+        }
+        block.push_str(HOSTS_BLOCK_END);
+        block.push_str(eol);
+        updated.extend_from_slice(block.as_bytes());
+
+        if updated == content {
+            return Ok(());
+        }
+        ops.write(&path, updated)
+    }
+
+    fn verify(&self, _ctx: &StrategyContext, options: &Value) -> StrategyStatus {
+        let path = self.hosts_path(options);
+        let mut evidence = FileEvidence::observe(&path);
+
+        let hosts = self
+            .read_hosts(&path, &FsOps::execute())
+            .and_then(|content| strip_hosts_block(&content));
+        let (state, detail) = match (hosts, self.domains(options)) {
+            (Ok((_, blocked)), Ok(domains)) => {
+                evidence.value = Some(format!("{} blocked domain(s)", blocked.len()));
+                let missing: Vec<&str> = domains
+                    .iter()
+                    .filter(|d| !blocked.iter().any(|b| b == *d))
+                    .copied()
+                    .collect();
+                if blocked.is_empty() {
+                    (
+                        MechanismState::Missing,
+                        "No managed block in hosts file".to_string(),
+                    )
+                } else if missing.is_empty() {
+                    (
+                        MechanismState::Active,
+                        format!("{} domain(s) blocked", blocked.len()),
+                    )
+                } else {
+                    (
+                        MechanismState::Modified,
+                        format!("Not blocked: {}", missing.join(", ")),
+                    )
+                }
+            }
+            (Err(e), _) => (MechanismState::UnexpectedContent, e),
+            (_, Err(e)) => (MechanismState::Missing, e),
+        };
+
+        StrategyStatus::new(self, state, vec![detail], vec![evidence])
+    }
+
+    fn revert(
+        &self,
+        _ctx: &StrategyContext,
+        options: &Value,
+        _manifest: &mut ProtectionManifest,
+        ops: &mut FsOps,
+        logs: &mut Vec<String>,
+    ) -> Result<(), String> {
+        let path = self.hosts_path(options);
+        let content = self.read_hosts(&path, ops)?;
+        let (stripped, blocked) = strip_hosts_block(&content)?;
+        if stripped == content {
+            return Ok(());
+        }
+        logs.push("Removing hosts file block...".to_string());
+        ops.write(&path, stripped)?;
+        logs.push(format!("[OK] Unblocked {} domain(s)", blocked.len()));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::scratch_dir;
+    use serde_json::json;
+
+    fn hosts_fixture(name: &str, content: &[u8]) -> (StrategyContext, Value, PathBuf) {
+        let dir = scratch_dir(name);
+        let hosts = dir.join("hosts");
+        fs::write(&hosts, content).unwrap();
+        let ctx = StrategyContext {
+            capcut_root: dir.clone(),
+            apps_path: dir.join("Apps"),
+        };
+        let options = json!({ "hosts_path": hosts, "lists": ["update", "telemetry"] });
+        (ctx, options, hosts)
+    }
+
+    fn apply(ctx: &StrategyContext, options: &Value) -> Result<(), String> {
+        HostsBlockStrategy.apply(
+            ctx,
+            options,
+            &mut ProtectionManifest::default(),
+            &mut FsOps::execute(),
+        )
+    }
+
+    fn revert(ctx: &StrategyContext, options: &Value) -> Result<(), String> {
+        HostsBlockStrategy.revert(
+            ctx,
+            options,
+            &mut ProtectionManifest::default(),
+            &mut FsOps::execute(),
+            &mut Vec::new(),
+        )
+    }
+
+    #[test]
+    fn hosts_apply_is_idempotent_and_revert_keeps_foreign_lines() {
+        // Latin-1 comment from another tool: not valid UTF-8
+        let original = b"127.0.0.1 localhost\r\n# caf\xe9 proxy\r\n10.0.0.5 intranet";
+        let (ctx, options, hosts) = hosts_fixture("hosts-roundtrip", original);
+
+        apply(&ctx, &options).unwrap();
+        let applied = fs::read(&hosts).unwrap();
+        let joined = format!("10.0.0.5 intranet\r\n{}\r\n", HOSTS_BLOCK_BEGIN_JOINED);
+        assert!(applied.starts_with(b"127.0.0.1 localhost\r\n# caf\xe9 proxy\r\n"));
+        assert!(applied
+            .windows(joined.len())
+            .any(|w| w == joined.as_bytes()));
+        assert!(applied.ends_with(format!("{}\r\n", HOSTS_BLOCK_END).as_bytes()));
+        assert!(HostsBlockStrategy.verify(&ctx, &options).active);
+
+        apply(&ctx, &options).unwrap();
+        assert_eq!(fs::read(&hosts).unwrap(), applied);
+
+        revert(&ctx, &options).unwrap();
+        let reverted = fs::read(&hosts).unwrap();
+        assert_eq!(reverted, original);
+        assert!(!HostsBlockStrategy.verify(&ctx, &options).active);
+
+        revert(&ctx, &options).unwrap();
+        assert_eq!(fs::read(&hosts).unwrap(), reverted);
+    }
+
+    #[test]
+    fn hosts_revert_keeps_a_final_line_break() {
+        let original = "127.0.0.1 localhost\n";
+        let (ctx, options, hosts) = hosts_fixture("hosts-final-eol", original.as_bytes());
+
+        apply(&ctx, &options).unwrap();
+        let applied = fs::read_to_string(&hosts).unwrap();
+        assert!(applied.starts_with(&format!("{}{}\n", original, HOSTS_BLOCK_BEGIN)));
+        revert(&ctx, &options).unwrap();
+        assert_eq!(fs::read_to_string(&hosts).unwrap(), original);
+    }
+
+    #[test]
+    fn hosts_block_without_end_marker_is_left_alone() {
+        let original = format!(
+            "127.0.0.1 localhost\n{}\n0.0.0.0 update.capcut.com\n10.0.0.5 intranet\n",
+            HOSTS_BLOCK_BEGIN
+        );
+        let (ctx, options, hosts) = hosts_fixture("hosts-truncated", original.as_bytes());
+
+        assert!(revert(&ctx, &options).is_err());
+        assert!(apply(&ctx, &options).is_err());
+        assert_eq!(fs::read_to_string(&hosts).unwrap(), original);
+    }
+
+    #[test]
+    fn hosts_apply_creates_a_missing_file() {
+        let (ctx, options, hosts) = hosts_fixture("hosts-missing", b"");
+        fs::remove_file(&hosts).unwrap();
+
+        apply(&ctx, &options).unwrap();
+        let content = fs::read_to_string(&hosts).unwrap();
+        assert!(content.starts_with(HOSTS_BLOCK_BEGIN));
+        assert!(content.contains("0.0.0.0 log.capcut.com\n"));
+    }
+}
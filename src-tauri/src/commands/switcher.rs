@@ -1,104 +1,551 @@
-use crate::commands::scanner::{get_capcut_apps_path, get_capcut_root_path};
+use crate::commands::fsops::{ChangePlan, FsOps};
+use crate::commands::ini::{self, IniDocument};
+use crate::commands::manifest::ProtectionManifest;
+use crate::commands::productinfo::{self, ProductInfo};
+use crate::commands::protector::{self, ProtectionVerdict};
+use crate::commands::strategy::{self, StrategyContext, StrategySpec};
+use crate::commands::transaction::{SystemState, Transaction, UndoAction};
+use crate::commands::watcher;
 use serde::Serialize;
-use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Serialize)]
 pub struct SwitchResult {
     pub success: bool,
     pub message: String,
     pub logs: Vec<String>,
+    /// Version the launcher pointed to before the switch
+    pub previous_version: Option<String>,
+    /// Version the launcher points to now (the previous one after a rollback)
+    pub new_version: Option<String>,
+    pub final_state: SystemState,
+    /// Undo steps that failed during rollback
+    pub rollback_errors: Vec<String>,
+    /// Protection strategies lifted for the switch and re-applied afterwards
+    pub reapplied_protections: Vec<String>,
+    /// Overall protection state once the switch finished (None for plans)
+    pub protection_verdict: Option<ProtectionVerdict>,
 }
 
 #[tauri::command]
 pub fn switch_version(target_path: String) -> SwitchResult {
+    let _paused = watcher::pause();
+    switch_version_with(target_path, &mut FsOps::execute())
+}
+
+/// Plan a version switch without touching disk
+#[tauri::command]
+pub fn plan_switch_version(target_path: String) -> ChangePlan {
+    let mut ops = FsOps::plan();
+    let result = switch_version_with(target_path, &mut ops);
+    let outcome = if result.success {
+        Ok(())
+    } else {
+        Err(result.message)
+    };
+    ops.into_plan(outcome, result.logs)
+}
+
+/// Check the target is an installed version folder the launcher can start
+fn validate_target(target_dir: &Path) -> Result<(), String> {
+    if !target_dir.is_dir() {
+        return Err("Target version not found".to_string());
+    }
+    if !target_dir.join("CapCut.exe").is_file() {
+        return Err("Target folder does not contain CapCut.exe".to_string());
+    }
+    Ok(())
+}
+
+/// Version the launcher currently points to (ProductInfo.xml, then configure.ini)
+pub fn active_version(apps_path: &Path) -> Option<String> {
+    productinfo::launcher_target(apps_path)
+        .and_then(|t| t.version)
+        .or_else(|| {
+            IniDocument::load(&apps_path.join("configure.ini"))
+                .ok()?
+                .get(ini::CONFIGURE_SECTION, "last_version")
+                .map(str::to_string)
+        })
+}
+
+/// Version recorded in the ProductInfo.xml original kept by the protection manifest
+/// (the live file is an empty blocker while protection is active)
+fn protected_version(manifest: &ProtectionManifest, apps_path: &Path) -> Option<String> {
+    let bytes = manifest.original_bytes(&productinfo::product_info_path(apps_path))?;
+    ProductInfo::parse(std::str::from_utf8(&bytes).ok()?)
+        .ok()?
+        .active_version()
+}
+
+/// Version the launcher will start, looking through protection blockers
+pub fn launcher_version(apps_path: &Path, manifest: &ProtectionManifest) -> Option<String> {
+    productinfo::launcher_target(apps_path)
+        .and_then(|t| t.version)
+        .or_else(|| protected_version(manifest, apps_path))
+        .or_else(|| active_version(apps_path))
+}
+
+/// Point ProductInfo.xml at the target executable
+fn write_product_info(
+    path: &Path,
+    target_exe: &Path,
+    version: &str,
+    ops: &mut FsOps,
+    logs: &mut Vec<String>,
+) -> Result<(), String> {
+    logs.push(format!("Updating ProductInfo at: {:?}", path));
+
+    // Keep whatever else CapCut wrote; only the target changes
+    // Read through ops so a dry run sees the original a planned lift restores
+    let loaded = ops
+        .read(path)
+        .map_err(|e| format!("Could not read ProductInfo.xml: {}", e))
+        .and_then(|bytes| ProductInfo::parse_bytes(&bytes));
+    let mut info = match loaded {
+        Ok(info) => info,
+        Err(e) => {
+            logs.push(format!("Starting a new ProductInfo.xml ({})", e));
+            ProductInfo::new()
+        }
+    };
+    info.set_target(target_exe, version);
+
+    // Remove Read-Only if present
+    if ops.is_readonly(path) == Some(true) {
+        ops.set_readonly(path, false)?;
+        logs.push("Removed Read-Only attribute from ProductInfo.xml".to_string());
+    }
+
+    ops.write(path, info.to_xml())
+        .map_err(|e| format!("Failed to write ProductInfo.xml: {}", e))?;
+    logs.push("[OK] Updated ProductInfo.xml".to_string());
+    Ok(())
+}
+
+/// Set last_version in configure.ini, keeping everything else in the file
+fn write_configure(
+    path: &Path,
+    version: &str,
+    ops: &mut FsOps,
+    logs: &mut Vec<String>,
+) -> Result<(), String> {
+    logs.push(format!("Updating configure.ini at: {:?}", path));
+
+    let mut config = strategy::load_ini(path, ops)?;
+    config.set(ini::CONFIGURE_SECTION, "last_version", version);
+
+    if ops.exists(path) {
+        ops.set_readonly(path, false)?;
+    }
+    ops.write(path, config.to_bytes())
+        .map_err(|e| format!("Failed to write configure.ini: {}", e))?;
+    logs.push("[OK] Updated configure.ini".to_string());
+    Ok(())
+}
+
+/// Re-read both files and confirm they point at the target
+fn verify_switch(
+    product_info_path: &Path,
+    config_path: &Path,
+    target_exe: &Path,
+    version: &str,
+) -> Result<(), String> {
+    let info = ProductInfo::load(product_info_path)?;
+    let expected_exe = target_exe.to_string_lossy();
+    if info.install_path() != Some(expected_exe.as_ref()) || info.version() != Some(version) {
+        return Err("ProductInfo.xml does not point to the new version after writing".to_string());
+    }
+
+    let config = IniDocument::load(config_path)?;
+    if config.get(ini::CONFIGURE_SECTION, "last_version") != Some(version) {
+        return Err("configure.ini does not contain the new version after writing".to_string());
+    }
+    Ok(())
+}
+
+/// Snapshot, write and verify the launcher files, lifting and re-applying
+/// any protection that owns them
+fn run_switch_steps(
+    txn: &mut Transaction,
+    switch: &SwitchPlan,
+    manifest: &mut ProtectionManifest,
+    ops: &mut FsOps,
+    logs: &mut Vec<String>,
+) -> Result<(), String> {
+    let product_info_path = productinfo::product_info_path(&switch.ctx.apps_path);
+    let config_path = switch.ctx.apps_path.join("configure.ini");
+    let target_exe = switch.target_dir.join("CapCut.exe");
+
+    let mut touched = vec![product_info_path.clone(), config_path.clone()];
+    for spec in &switch.lifted {
+        if let Some(strategy) = strategy::strategy_by_id(&spec.id) {
+            touched.extend(strategy.touched_paths(&switch.ctx, &spec.options));
+        }
+    }
+    txn.snapshot(&touched, ops)?;
+    txn.register(
+        "Launcher files",
+        UndoAction::RestoreFiles { paths: touched },
+    )?;
+
+    // 1. Lift protection so the launcher files hold their real content again
+    for spec in switch.lifted.iter().rev() {
+        protector::lift_strategy(&switch.ctx, spec, manifest, ops, logs)?;
+    }
+
+    // 2. ProductInfo.xml is how the CapCut launcher knows which EXE to run
+    write_product_info(&product_info_path, &target_exe, &switch.version, ops, logs)?;
+
+    // 3. configure.ini (last_version)
+    write_configure(&config_path, &switch.version, ops, logs)?;
+
+    // 4. Re-read both files to confirm the launcher will start the target
+    if !ops.is_dry_run() {
+        verify_switch(
+            &product_info_path,
+            &config_path,
+            &target_exe,
+            &switch.version,
+        )?;
+        logs.push("[OK] Verified launcher files".to_string());
+    }
+
+    // 5. Re-apply the same protection for the new version
+    for spec in &switch.lifted {
+        protector::apply_strategy(&switch.ctx, spec, manifest, ops, logs)?;
+        if ops.is_dry_run() {
+            continue;
+        }
+        let status =
+            strategy::strategy_by_id(&spec.id).map(|s| s.verify(&switch.ctx, &spec.options));
+        if let Some(status) = status.filter(|s| !s.active) {
+            return Err(format!(
+                "{} could not be re-applied: {}",
+                status.name,
+                status.details.join("; ")
+            ));
+        }
+    }
+    if !ops.is_dry_run() {
+        manifest.save()?;
+    }
+    Ok(())
+}
+
+/// What a switch will do
+struct SwitchPlan {
+    ctx: StrategyContext,
+    target_dir: PathBuf,
+    version: String,
+    /// Active protections that own the launcher files
+    lifted: Vec<StrategySpec>,
+}
+
+fn switch_version_with(target_path: String, ops: &mut FsOps) -> SwitchResult {
     let mut logs = Vec::new();
     let target_dir = PathBuf::from(&target_path);
 
     logs.push(format!("Initiating switch to version at: {:?}", target_dir));
 
-    if !target_dir.exists() {
-        logs.push("[!] Target directory does not exist".to_string());
-        return SwitchResult {
-            success: false,
-            message: "Target version not found".to_string(),
-            logs,
-        };
+    let fail = |message: String, logs: Vec<String>, previous: Option<String>| SwitchResult {
+        success: false,
+        message,
+        logs,
+        new_version: previous.clone(),
+        previous_version: previous,
+        final_state: SystemState::Original,
+        rollback_errors: vec![],
+        reapplied_protections: vec![],
+        protection_verdict: None,
+    };
+
+    let ctx = match protector::strategy_context() {
+        Ok(ctx) => ctx,
+        Err(e) => {
+            logs.push(format!("[!] {}", e));
+            return fail(e, logs, None);
+        }
+    };
+    let mut manifest = match ProtectionManifest::open_default() {
+        Ok(m) => m,
+        Err(e) => return fail(e, logs, None),
+    };
+    let previous = launcher_version(&ctx.apps_path, &manifest);
+
+    if let Err(e) = validate_target(&target_dir) {
+        logs.push(format!("[!] {}", e));
+        return fail(e, logs, previous);
     }
 
     // Identify version from path (e.g. .../3.1.0.100)
     let version_name = target_dir
         .file_name()
         .and_then(|n| n.to_str())
-        .unwrap_or("unknown");
+        .unwrap_or("unknown")
+        .to_string();
 
     logs.push(format!("Detected version: {}", version_name));
+    if let Some(prev) = &previous {
+        logs.push(format!("Launcher currently points to: {}", prev));
+    }
 
-    // 1. Update ProductInfo.xml to point to this version
-    // This is how CapCut launcher knows which EXE to run
-    if let Some(root_path) = get_capcut_root_path() {
-        let product_info_path = root_path.join("Apps").join("ProductInfo.xml");
-        logs.push(format!("Updating ProductInfo at: {:?}", product_info_path));
-
-        let target_exe = target_dir.join("CapCut.exe");
-
-        // Simple XML replacer (robust enough for this specific file)
-        let new_content = format!(
-            r#"<?xml version="1.0" charset="utf-8"?>
-<ProductInfo>
-  <InstallPath>{}</InstallPath>
-  <Version>{}</Version>
-</ProductInfo>"#,
-            target_exe.to_string_lossy(),
-            version_name
-        );
+    // Protection that owns the launcher files is lifted and re-applied afterwards
+    let launcher_files = [
+        productinfo::product_info_path(&ctx.apps_path),
+        ctx.apps_path.join("configure.ini"),
+    ];
+    // (including recorded protection that has drifted, so the switch repairs it)
+    let mut candidates = protector::active_specs(&ctx, &manifest);
+    for spec in &manifest.applied {
+        if !candidates.iter().any(|c| c.id == spec.id) {
+            candidates.push(spec.clone());
+        }
+    }
+    let lifted: Vec<StrategySpec> = candidates
+        .into_iter()
+        .filter(|spec| {
+            strategy::strategy_by_id(&spec.id)
+                .map(|s| {
+                    s.touched_paths(&ctx, &spec.options)
+                        .iter()
+                        .any(|p| launcher_files.contains(p))
+                })
+                .unwrap_or(false)
+        })
+        .collect();
+    if !lifted.is_empty() {
+        let ids: Vec<&str> = lifted.iter().map(|s| s.id.as_str()).collect();
+        logs.push(format!("Active protection to re-apply: {}", ids.join(", ")));
+    }
+
+    let switch = SwitchPlan {
+        ctx,
+        target_dir,
+        version: version_name,
+        lifted,
+    };
 
-        // Remove Read-Only if present
-        if let Ok(metadata) = fs::metadata(&product_info_path) {
-            let mut perms = metadata.permissions();
-            if perms.readonly() {
-                perms.set_readonly(false);
-                let _ = fs::set_permissions(&product_info_path, perms);
-                logs.push("Removed Read-Only attribute from ProductInfo.xml".to_string());
-            }
+    let mut txn = if ops.is_dry_run() {
+        Transaction::planned()
+    } else {
+        match Transaction::begin(&mut logs) {
+            Ok(t) => t,
+            Err(e) => return fail(e, logs, previous),
         }
+    };
+    let manifest_before = manifest.clone();
 
-        match fs::write(&product_info_path, new_content) {
-            Ok(_) => logs.push("[OK] Updated ProductInfo.xml".to_string()),
-            Err(e) => logs.push(format!("[!] Failed to write ProductInfo.xml: {}", e)),
+    if let Err(e) = run_switch_steps(&mut txn, &switch, &mut manifest, ops, &mut logs) {
+        logs.push(format!("[!] {}", e));
+        if ops.is_dry_run() {
+            return fail(e, logs, previous);
+        }
+        let mut rollback_errors = txn.rollback(&mut logs);
+        // The restored files match the manifest as it was before the switch
+        if let Err(e) = manifest_before.save() {
+            rollback_errors.push(format!("Protection manifest: {}", e));
         }
+        let final_state = if rollback_errors.is_empty() {
+            logs.push("[OK] Rolled back - launcher and protection unchanged".to_string());
+            SystemState::Original
+        } else {
+            SystemState::Inconsistent
+        };
+        return SwitchResult {
+            success: false,
+            message: format!("Switch failed: {}", e),
+            logs,
+            new_version: previous.clone(),
+            previous_version: previous,
+            final_state,
+            rollback_errors,
+            reapplied_protections: vec![],
+            protection_verdict: Some(protector::check_protection_status().verdict),
+        };
     }
 
-    // 2. Update configure.ini (last_version)
-    if let Some(apps_path) = get_capcut_apps_path() {
-        let config_path = apps_path.join("configure.ini");
-        logs.push(format!("Updating configure.ini at: {:?}", config_path));
+    let protection_verdict = if ops.is_dry_run() {
+        None
+    } else {
+        txn.commit();
+        Some(protector::check_protection_status().verdict)
+    };
 
-        let config_content = format!("[Configure]\r\nlast_version={}\r\n", version_name);
+    let reapplied: Vec<String> = switch.lifted.iter().map(|s| s.id.clone()).collect();
+    let message = if reapplied.is_empty() {
+        format!("Successfully switched to v{}", switch.version)
+    } else {
+        format!(
+            "Successfully switched to v{} - protection re-applied",
+            switch.version
+        )
+    };
 
-        // Remove Read-Only if present
-        if let Ok(metadata) = fs::metadata(&config_path) {
-            let mut perms = metadata.permissions();
-            if perms.readonly() {
-                perms.set_readonly(false);
-                let _ = fs::set_permissions(&config_path, perms);
-            }
+    SwitchResult {
+        success: true,
+        message,
+        logs,
+        previous_version: previous,
+        new_version: Some(switch.version),
+        final_state: SystemState::Target,
+        rollback_errors: vec![],
+        reapplied_protections: reapplied,
+        protection_verdict,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::fsops::{ChangeAction, PlannedChange};
+    use crate::commands::test_support::{scratch_install, scratch_local_app_data};
+    use std::fs;
+
+    fn steps(changes: &[PlannedChange]) -> Vec<(ChangeAction, String)> {
+        changes.iter().map(|c| (c.action, c.path.clone())).collect()
+    }
+
+    #[test]
+    fn switch_lifts_and_reapplies_active_protection() {
+        let (_guard, dir) = scratch_local_app_data("switch-reapply");
+        let apps = scratch_install(&dir, &["5.0.0.100", "5.1.0.200"], "5.0.0.100");
+        let specs = [
+            StrategySpec::new("config_lock"),
+            StrategySpec::new("blocker_files"),
+        ];
+        assert!(protector::apply_strategies(&specs).success);
+        let product_info = productinfo::product_info_path(&apps);
+        assert_eq!(fs::metadata(&product_info).unwrap().len(), 0);
+
+        let target = apps.join("5.1.0.200").to_string_lossy().to_string();
+        let plan = plan_switch_version(target.clone());
+        assert!(plan.success, "{:?}", plan.error);
+        // The planned lift restores the real ProductInfo.xml before it is rewritten
+        assert!(
+            !plan.logs.iter().any(|l| l.starts_with("Starting a new")),
+            "{:?}",
+            plan.logs
+        );
+
+        let mut ops = FsOps::execute();
+        let result = switch_version_with(target, &mut ops);
+        assert!(result.success, "{}: {:?}", result.message, result.logs);
+        let run = ops.into_plan(Ok(()), Vec::new());
+        assert_eq!(steps(&plan.changes), steps(&run.changes));
+
+        assert_eq!(result.previous_version.as_deref(), Some("5.0.0.100"));
+        assert_eq!(result.new_version.as_deref(), Some("5.1.0.200"));
+        assert_eq!(
+            result.reapplied_protections,
+            ["config_lock", "blocker_files"]
+        );
+        assert_eq!(result.protection_verdict, Some(ProtectionVerdict::Full));
+
+        // Both mechanisms are back, and their recorded originals point at the new version
+        let ctx = protector::strategy_context().unwrap();
+        for spec in &specs {
+            let strategy = strategy::strategy_by_id(&spec.id).unwrap();
+            assert!(strategy.verify(&ctx, &spec.options).active, "{}", spec.id);
         }
+        let manifest = ProtectionManifest::open_default().unwrap();
+        let original = manifest.original_bytes(&product_info).unwrap();
+        let info = ProductInfo::parse_bytes(&original).unwrap();
+        assert_eq!(info.version(), Some("5.1.0.200"));
+        let config = manifest
+            .original_bytes(&apps.join("configure.ini"))
+            .unwrap();
+        let config = IniDocument::parse_bytes(&config).unwrap();
+        assert_eq!(
+            config.get(ini::CONFIGURE_SECTION, "last_version"),
+            Some("5.1.0.200")
+        );
+        assert_eq!(config.get(ini::CONFIGURE_SECTION, "language"), Some("en"));
+        assert_eq!(
+            launcher_version(&apps, &manifest).as_deref(),
+            Some("5.1.0.200")
+        );
+    }
+
+    #[test]
+    fn switch_reports_previous_and_new_version() {
+        let (_guard, dir) = scratch_local_app_data("switch-versions");
+        let apps = scratch_install(&dir, &["5.0.0.100", "5.1.0.200"], "5.0.0.100");
+
+        let result = switch_version_with(
+            apps.join("5.1.0.200").to_string_lossy().to_string(),
+            &mut FsOps::execute(),
+        );
+        assert!(result.success, "{}: {:?}", result.message, result.logs);
+        assert_eq!(result.previous_version.as_deref(), Some("5.0.0.100"));
+        assert_eq!(result.new_version.as_deref(), Some("5.1.0.200"));
+        assert_eq!(result.final_state, SystemState::Target);
+        assert!(result.reapplied_protections.is_empty());
 
-        match fs::write(&config_path, config_content) {
-            Ok(_) => logs.push("[OK] Updated configure.ini".to_string()),
-            Err(e) => logs.push(format!("[!] Failed to write configure.ini: {}", e)),
+        let info = ProductInfo::load(&productinfo::product_info_path(&apps)).unwrap();
+        assert_eq!(info.version(), Some("5.1.0.200"));
+        let config = IniDocument::load(&apps.join("configure.ini")).unwrap();
+        assert_eq!(
+            config.get(ini::CONFIGURE_SECTION, "last_version"),
+            Some("5.1.0.200")
+        );
+    }
+
+    #[test]
+    fn switch_rejects_a_folder_without_capcut_exe() {
+        let (_guard, dir) = scratch_local_app_data("switch-invalid");
+        let apps = scratch_install(&dir, &["5.0.0.100", "5.1.0.200"], "5.0.0.100");
+        fs::create_dir_all(apps.join("5.2.0.300")).unwrap();
+        let before = fs::read(productinfo::product_info_path(&apps)).unwrap();
+
+        for target in [apps.join("5.2.0.300"), apps.join("9.9.9.999")] {
+            let result =
+                switch_version_with(target.to_string_lossy().to_string(), &mut FsOps::execute());
+            assert!(!result.success);
+            assert_eq!(result.previous_version.as_deref(), Some("5.0.0.100"));
+            assert_eq!(result.new_version, result.previous_version);
         }
+        let plan = plan_switch_version(apps.join("5.2.0.300").to_string_lossy().to_string());
+        assert_eq!(
+            plan.error.as_deref(),
+            Some("Target folder does not contain CapCut.exe")
+        );
+        assert!(plan.changes.is_empty());
+        assert_eq!(
+            fs::read(productinfo::product_info_path(&apps)).unwrap(),
+            before
+        );
     }
 
-    // 3. Ensure target is not renamed to backup
-    // Some older versions might have been renamed by the protector previously
-    // This function assumes we are dealing with standard folders, but we could add logic
-    // to rename "_backup" folders back to normal if needed.
+    #[test]
+    fn failed_write_restores_the_launcher_files() {
+        let (_guard, dir) = scratch_local_app_data("switch-rollback");
+        let apps = scratch_install(&dir, &["5.0.0.100", "5.1.0.200"], "5.0.0.100");
+        let product_info = productinfo::product_info_path(&apps);
+        let before = fs::read(&product_info).unwrap();
+        // configure.ini cannot be written once ProductInfo.xml already was
+        let config = apps.join("configure.ini");
+        fs::remove_file(&config).unwrap();
+        fs::create_dir_all(config.join("locked")).unwrap();
 
-    SwitchResult {
-        success: true,
-        message: format!("Successfully switched to v{}", version_name),
-        logs,
+        let result = switch_version_with(
+            apps.join("5.1.0.200").to_string_lossy().to_string(),
+            &mut FsOps::execute(),
+        );
+        assert!(!result.success);
+        assert!(
+            result.message.contains("configure.ini"),
+            "{}",
+            result.message
+        );
+        assert_eq!(result.final_state, SystemState::Original);
+        assert!(
+            result.rollback_errors.is_empty(),
+            "{:?}",
+            result.rollback_errors
+        );
+        assert_eq!(result.new_version.as_deref(), Some("5.0.0.100"));
+        assert_eq!(fs::read(&product_info).unwrap(), before);
+        assert!(config.join("locked").is_dir());
     }
 }
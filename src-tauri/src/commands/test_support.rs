@@ -0,0 +1,253 @@
+//! Test helpers
+//! Scratch directories, scratch CapCut installs and a minimal local HTTP server standing in for
+//! catalog and download hosts
+
+use serde::de::{Deserializer, IgnoredAny, MapAccess, Visitor};
+use serde::Serialize;
+use std::ffi::OsString;
+use std::fmt;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use super::productinfo::{self, ProductInfo};
+
+/// Serializes tests that point LOCALAPPDATA at a scratch directory
+static DATA_DIR_LOCK: Mutex<()> = Mutex::new(());
+
+/// Empty scratch directory unique to this test run
+pub fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ccvg-test-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Keeps LOCALAPPDATA pointed at a scratch directory; the previous value is restored on drop
+pub struct DataDirGuard {
+    previous: Option<OsString>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Drop for DataDirGuard {
+    fn drop(&mut self) {
+        match self.previous.take() {
+            Some(value) => std::env::set_var("LOCALAPPDATA", value),
+            None => std::env::remove_var("LOCALAPPDATA"),
+        }
+    }
+}
+
+/// Point LOCALAPPDATA (and so the Guard data directory) at a scratch directory.
+/// Hold the returned guard for the whole test.
+pub fn scratch_local_app_data(name: &str) -> (DataDirGuard, PathBuf) {
+    let lock = DATA_DIR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let previous = std::env::var_os("LOCALAPPDATA");
+    let dir = scratch_dir(name);
+    std::env::set_var("LOCALAPPDATA", &dir);
+    (
+        DataDirGuard {
+            previous,
+            _lock: lock,
+        },
+        dir,
+    )
+}
+
+/// A CapCut install under a scratch LOCALAPPDATA with the given version folders,
+/// the launcher pointing at `active`. Returns the Apps folder.
+pub fn scratch_install(local_app_data: &Path, versions: &[&str], active: &str) -> PathBuf {
+    let apps = local_app_data.join("CapCut").join("Apps");
+    for version in versions {
+        std::fs::create_dir_all(apps.join(version)).unwrap();
+        std::fs::write(apps.join(version).join("CapCut.exe"), b"MZ").unwrap();
+    }
+    let mut info = ProductInfo::new();
+    info.set_target(&apps.join(active).join("CapCut.exe"), active);
+    std::fs::write(productinfo::product_info_path(&apps), info.to_xml()).unwrap();
+    std::fs::write(
+        apps.join("configure.ini"),
+        format!("[Configure]\r\nlanguage=en\r\nlast_version={}\r\n", active),
+    )
+    .unwrap();
+    apps
+}
+
+/// Top-level keys of a value serialized as a JSON object, duplicates included
+pub fn json_keys(value: &impl Serialize) -> Vec<String> {
+    struct Keys;
+
+    impl<'de> Visitor<'de> for Keys {
+        type Value = Vec<String>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a JSON object")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut keys = Vec::new();
+            while let Some((key, IgnoredAny)) = map.next_entry::<String, IgnoredAny>()? {
+                keys.push(key);
+            }
+            Ok(keys)
+        }
+    }
+
+    let json = serde_json::to_string(value).unwrap();
+    serde_json::Deserializer::from_str(&json)
+        .deserialize_map(Keys)
+        .unwrap()
+}
+
+/// A request received by the test server
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub path: String,
+    /// Start of a `Range: bytes=N-` header
+    pub range_start: Option<u64>,
+}
+
+/// What the test server answers
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: Vec<u8>,
+    /// Drop the connection after this many body bytes (Content-Length still announces all of them)
+    pub cut_after: Option<usize>,
+    /// Pause between 8 KiB chunks of the body
+    pub chunk_delay: Option<Duration>,
+}
+
+impl Response {
+    pub fn ok(body: &[u8]) -> Self {
+        Self::status(200).body(body)
+    }
+
+    pub fn status(status: u16) -> Self {
+        Response {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+            cut_after: None,
+            chunk_delay: None,
+        }
+    }
+
+    pub fn body(mut self, body: &[u8]) -> Self {
+        self.body = body.to_vec();
+        self
+    }
+
+    pub fn header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+
+    /// 206 with the bytes of `full` from `start`
+    pub fn partial(full: &[u8], start: usize) -> Self {
+        Self::status(206)
+            .header(
+                "Content-Range",
+                format!("bytes {}-{}/{}", start, full.len() - 1, full.len()),
+            )
+            .body(&full[start..])
+    }
+
+    pub fn cut_after(mut self, bytes: usize) -> Self {
+        self.cut_after = Some(bytes);
+        self
+    }
+
+    pub fn slow(mut self, chunk_delay: Duration) -> Self {
+        self.chunk_delay = Some(chunk_delay);
+        self
+    }
+}
+
+/// A local HTTP server answering every request with a handler
+pub struct TestServer {
+    base: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl TestServer {
+    /// Serve requests on an ephemeral port; the handler also gets the request's index
+    pub fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&Request, usize) -> Response + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                reader.read_line(&mut line).ok();
+                let path = line.split_whitespace().nth(1).unwrap_or("/").to_string();
+                let mut range_start = None;
+                loop {
+                    let mut header = String::new();
+                    if reader.read_line(&mut header).unwrap_or(0) == 0 || header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some(value) = header.to_ascii_lowercase().strip_prefix("range: bytes=") {
+                        range_start = value.trim().trim_end_matches('-').parse().ok();
+                    }
+                }
+
+                let request = Request { path, range_start };
+                let index = {
+                    let mut log = log.lock().unwrap();
+                    log.push(request.clone());
+                    log.len() - 1
+                };
+                let response = handler(&request, index);
+
+                let mut head = format!(
+                    "HTTP/1.1 {} Test\r\nContent-Length: {}\r\nConnection: close\r\n",
+                    response.status,
+                    response.body.len()
+                );
+                for (name, value) in &response.headers {
+                    head.push_str(&format!("{}: {}\r\n", name, value));
+                }
+                head.push_str("\r\n");
+                if stream.write_all(head.as_bytes()).is_err() {
+                    continue;
+                }
+                let body = &response.body[..response
+                    .cut_after
+                    .unwrap_or(usize::MAX)
+                    .min(response.body.len())];
+                for chunk in body.chunks(8 * 1024) {
+                    if stream.write_all(chunk).is_err() {
+                        break;
+                    }
+                    if let Some(delay) = response.chunk_delay {
+                        std::thread::sleep(delay);
+                    }
+                }
+            }
+        });
+
+        TestServer { base, requests }
+    }
+
+    /// Absolute URL of a path on this server
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base, path)
+    }
+
+    /// Requests received so far
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
@@ -0,0 +1,280 @@
+//! Transactional execution for multi-step operations
+//! Each completed step registers an undo action; a later failure rolls them back in reverse order
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::backup;
+use super::fsops::FsOps;
+use super::manifest::ProtectionManifest;
+use super::paths;
+
+/// Where the system ended up after a transactional operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemState {
+    /// All steps applied
+    Target,
+    /// A step failed and every earlier step was undone
+    Original,
+    /// A step failed and rollback could not undo everything
+    Inconsistent,
+}
+
+/// Result of a transactional operation
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactionResult {
+    pub success: bool,
+    pub error: Option<String>,
+    pub logs: Vec<String>,
+    pub final_state: SystemState,
+    /// Undo steps that failed during rollback (empty unless final_state is Inconsistent)
+    pub rollback_errors: Vec<String>,
+}
+
+/// How to undo a completed step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UndoAction {
+    /// Put a deleted version folder back from its backup
+    RestoreBackup { backup_id: String },
+    /// Restore files to their state at the start of the transaction
+    RestoreFiles { paths: Vec<PathBuf> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UndoStep {
+    description: String,
+    action: UndoAction,
+}
+
+/// An in-progress transaction with its undo log
+pub struct Transaction {
+    undo: Vec<UndoStep>,
+    /// Snapshots of files as they were when the transaction started
+    journal: ProtectionManifest,
+    /// Where the undo log and snapshots are persisted (None when only planning)
+    dir: Option<PathBuf>,
+}
+
+/// Undo log kept beside the journal so a crashed transaction can be rolled back
+const UNDO_FILE: &str = "undo.json";
+
+/// Get the transaction journal directory
+fn get_journal_dir() -> Option<PathBuf> {
+    paths::get_guard_data_dir().map(|p| p.join("Transaction"))
+}
+
+impl Transaction {
+    /// Start a transaction with a fresh journal, first rolling back one left unfinished
+    pub fn begin(logs: &mut Vec<String>) -> Result<Self, String> {
+        let journal_dir = get_journal_dir().ok_or("Could not determine journal directory")?;
+        Self::begin_in(&journal_dir, logs)
+    }
+
+    /// Start a transaction journaling into a specific directory
+    pub fn begin_in(journal_dir: &Path, logs: &mut Vec<String>) -> Result<Self, String> {
+        // An undo log is only left behind by a transaction that crashed or could not roll back
+        if journal_dir.join(UNDO_FILE).exists() {
+            logs.push(
+                "[!] Found an unfinished operation from a previous run - rolling it back"
+                    .to_string(),
+            );
+            let errors = Self::resume(journal_dir)?.rollback(logs);
+            if !errors.is_empty() {
+                return Err(format!(
+                    "An unfinished operation from a previous run could not be rolled back ({}); its journal is kept in {}",
+                    errors.join("; "),
+                    journal_dir.display()
+                ));
+            }
+        }
+        if journal_dir.exists() {
+            fs::remove_dir_all(journal_dir)
+                .map_err(|e| format!("Could not reset transaction journal: {}", e))?;
+        }
+        Ok(Transaction {
+            undo: Vec::new(),
+            journal: ProtectionManifest::open(journal_dir)?,
+            dir: Some(journal_dir.to_path_buf()),
+        })
+    }
+
+    /// Reload a transaction persisted in a journal directory
+    fn resume(journal_dir: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(journal_dir.join(UNDO_FILE))
+            .map_err(|e| format!("Could not read unfinished transaction journal: {}", e))?;
+        let undo = serde_json::from_str(&content).map_err(|e| {
+            format!(
+                "Unfinished transaction journal in {} is unreadable: {}",
+                journal_dir.display(),
+                e
+            )
+        })?;
+        Ok(Transaction {
+            undo,
+            journal: ProtectionManifest::open(journal_dir)?,
+            dir: Some(journal_dir.to_path_buf()),
+        })
+    }
+
+    /// A transaction for planning only - nothing is journaled to disk
+    pub fn planned() -> Self {
+        Transaction {
+            undo: Vec::new(),
+            journal: ProtectionManifest::default(),
+            dir: None,
+        }
+    }
+
+    /// Persist the snapshots and undo log so a crash can be rolled back on the next start
+    fn save(&self) -> Result<(), String> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        self.journal.save()?;
+        let content = serde_json::to_string_pretty(&self.undo).map_err(|e| e.to_string())?;
+        fs::write(dir.join(UNDO_FILE), content)
+            .map_err(|e| format!("Failed to save transaction journal: {}", e))
+    }
+
+    /// Snapshot files that the next step will modify
+    pub fn snapshot(&mut self, paths: &[PathBuf], ops: &mut FsOps) -> Result<(), String> {
+        // The journal is internal bookkeeping, not part of a plan
+        if ops.is_dry_run() {
+            return Ok(());
+        }
+        for path in paths {
+            self.journal.record(path, ops)?;
+        }
+        self.save()
+    }
+
+    /// Register how to undo a step before it runs
+    pub fn register(
+        &mut self,
+        description: impl Into<String>,
+        action: UndoAction,
+    ) -> Result<(), String> {
+        self.undo.push(UndoStep {
+            description: description.into(),
+            action,
+        });
+        self.save()
+    }
+
+    /// Undo every registered step, newest first.
+    /// Returns the undo steps that failed.
+    pub fn rollback(self, logs: &mut Vec<String>) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut ops = FsOps::execute();
+        logs.push("Rolling back completed steps...".to_string());
+
+        for step in self.undo.iter().rev() {
+            logs.push(format!("Undoing: {}", step.description));
+            let result = match &step.action {
+                UndoAction::RestoreBackup { backup_id } => {
+                    let restore = backup::restore_backup(backup_id);
+                    if restore.success {
+                        Ok(())
+                    } else {
+                        Err(restore.error.unwrap_or_default())
+                    }
+                }
+                UndoAction::RestoreFiles { paths } => {
+                    let keys: Vec<String> = paths
+                        .iter()
+                        .map(|p| p.to_string_lossy().to_string())
+                        .collect();
+                    self.journal
+                        .restore_where(|e| keys.contains(&e.path), &mut ops, logs)
+                }
+            };
+            match result {
+                Ok(()) => logs.push(format!("[OK] Undone: {}", step.description)),
+                Err(e) => {
+                    logs.push(format!("[!] Could not undo {}: {}", step.description, e));
+                    errors.push(format!("{}: {}", step.description, e));
+                }
+            }
+        }
+
+        // Keep the journal around if something could not be restored
+        if errors.is_empty() {
+            self.journal.clear().ok();
+        }
+        errors
+    }
+
+    /// Finish the transaction, discarding the undo log
+    pub fn commit(self) {
+        self.journal.clear().ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::scratch_dir;
+
+    #[test]
+    fn begin_rolls_back_a_crashed_transaction() {
+        let dir = scratch_dir("transaction-crash");
+        let journal = dir.join("Transaction");
+        let config = dir.join("configure.ini");
+        fs::write(&config, "last_version=5.4.0").unwrap();
+
+        let mut ops = FsOps::execute();
+        let mut txn = Transaction::begin_in(&journal, &mut Vec::new()).unwrap();
+        txn.snapshot(std::slice::from_ref(&config), &mut ops)
+            .unwrap();
+        txn.register(
+            "Config lock",
+            UndoAction::RestoreFiles {
+                paths: vec![config.clone()],
+            },
+        )
+        .unwrap();
+        fs::write(&config, "last_version=1.0.0").unwrap();
+        // The process dies here: neither commit nor rollback runs
+        drop(txn);
+
+        let mut logs = Vec::new();
+        let txn = Transaction::begin_in(&journal, &mut logs).unwrap();
+        assert_eq!(fs::read_to_string(&config).unwrap(), "last_version=5.4.0");
+        assert!(logs.iter().any(|l| l.contains("unfinished operation")));
+        txn.commit();
+        assert!(!journal.exists());
+    }
+
+    #[test]
+    fn begin_refuses_an_unreadable_journal() {
+        let dir = scratch_dir("transaction-corrupt");
+        let journal = dir.join("Transaction");
+        fs::create_dir_all(&journal).unwrap();
+        fs::write(journal.join(UNDO_FILE), "not json").unwrap();
+
+        assert!(Transaction::begin_in(&journal, &mut Vec::new()).is_err());
+        assert!(journal.join(UNDO_FILE).exists());
+    }
+
+    #[test]
+    fn committed_transaction_leaves_nothing_to_replay() {
+        let dir = scratch_dir("transaction-commit");
+        let journal = dir.join("Transaction");
+        let config = dir.join("configure.ini");
+        fs::write(&config, "before").unwrap();
+
+        let mut txn = Transaction::begin_in(&journal, &mut Vec::new()).unwrap();
+        txn.snapshot(std::slice::from_ref(&config), &mut FsOps::execute())
+            .unwrap();
+        fs::write(&config, "after").unwrap();
+        txn.commit();
+
+        let mut logs = Vec::new();
+        Transaction::begin_in(&journal, &mut logs).unwrap();
+        assert_eq!(fs::read_to_string(&config).unwrap(), "after");
+        assert!(logs.is_empty());
+    }
+}
@@ -0,0 +1,383 @@
+//! Semantic CapCut version numbers
+//! Parses folder names, catalog labels and installer filenames into one totally ordered type
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Release channel of a build
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Channel {
+    /// Public beta ("Beta6")
+    Beta(u32),
+    /// Beta cut from a test build ("Test1 Beta2")
+    TestBeta { test: u32, beta: u32 },
+    /// Pre-release test build ("Test2")
+    Test(u32),
+    /// General release (also assumed when nothing else is known)
+    #[default]
+    Latest,
+}
+
+/// Coarse release track used for filtering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelKind {
+    Stable,
+    /// Public and test betas
+    Beta,
+    Test,
+}
+
+impl Channel {
+    /// Sort key: betas (test betas first) < tests < latest
+    fn key(&self) -> (u8, u32, u8, u32) {
+        match *self {
+            Channel::TestBeta { test, beta } => (0, beta, 0, test),
+            Channel::Beta(n) => (0, n, 1, 0),
+            Channel::Test(n) => (1, n, 0, 0),
+            Channel::Latest => (2, 0, 0, 0),
+        }
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        *self != Channel::Latest
+    }
+
+    pub fn kind(&self) -> ChannelKind {
+        match self {
+            Channel::Beta(_) | Channel::TestBeta { .. } => ChannelKind::Beta,
+            Channel::Test(_) => ChannelKind::Test,
+            Channel::Latest => ChannelKind::Stable,
+        }
+    }
+}
+
+impl Ord for Channel {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl PartialOrd for Channel {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Channel::Beta(n) => write!(f, "Beta{}", n),
+            Channel::TestBeta { test, beta } => write!(f, "Test{} Beta{}", test, beta),
+            Channel::Test(n) => write!(f, "Test{}", n),
+            Channel::Latest => write!(f, "Latest"),
+        }
+    }
+}
+
+/// A CapCut version: major.minor.patch, optional build number and channel.
+/// Ordered by number, then channel, then build; a version without a build (a catalog label)
+/// sorts just before builds of the same release and channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CapCutVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    pub build: Option<u32>,
+    pub channel: Channel,
+}
+
+impl Ord for CapCutVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| self.channel.cmp(&other.channel))
+            .then_with(|| self.build.cmp(&other.build))
+    }
+}
+
+impl PartialOrd for CapCutVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Split on the separators used by folders, labels and installer names
+fn tokens(s: &str) -> Vec<String> {
+    s.split(['.', '_', '-', ' ', '(', ')', '[', ']'])
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_ascii_lowercase())
+        .collect()
+}
+
+/// Parse "beta6" / "test2" (or a bare keyword followed by a number token)
+fn channel_token(token: &str, next: Option<&String>) -> Option<(&'static str, u32)> {
+    for keyword in ["beta", "test"] {
+        if let Some(rest) = token.strip_prefix(keyword) {
+            let number = if rest.is_empty() {
+                next.and_then(|n| n.parse().ok())
+            } else {
+                rest.parse().ok()
+            };
+            if let Some(n) = number {
+                return Some((keyword, n));
+            }
+        }
+    }
+    None
+}
+
+impl CapCutVersion {
+    pub fn new(major: u32, minor: u32, patch: u32) -> Self {
+        CapCutVersion {
+            major,
+            minor,
+            patch,
+            build: None,
+            channel: Channel::Latest,
+        }
+    }
+
+    /// Parse any of the forms CapCut versions appear in:
+    /// "5.3.0.1964", "5.4.0 (Beta6)", "5.3.0 (Test1 Beta2)",
+    /// "CapCut_5_4_0_1991_beta6_capcutpc_beta_creatortool.exe"
+    pub fn parse(s: &str) -> Option<Self> {
+        let tokens = tokens(s.trim_end_matches(".exe"));
+
+        // First run of numeric tokens is major.minor.patch[.build]
+        let start = tokens
+            .iter()
+            .position(|t| t.trim_start_matches('v').parse::<u32>().is_ok())?;
+        let numbers: Vec<u32> = tokens[start..]
+            .iter()
+            .enumerate()
+            .map_while(|(i, t)| {
+                let t = if i == 0 { t.trim_start_matches('v') } else { t };
+                t.parse().ok()
+            })
+            .take(4)
+            .collect();
+        if numbers.len() < 3 {
+            return None;
+        }
+
+        let rest = &tokens[start + numbers.len()..];
+        let mut beta = None;
+        let mut test = None;
+        for (i, token) in rest.iter().enumerate() {
+            match channel_token(token, rest.get(i + 1)) {
+                Some(("beta", n)) if beta.is_none() => beta = Some(n),
+                Some(("test", n)) if test.is_none() => test = Some(n),
+                _ => {}
+            }
+        }
+        let channel = match (test, beta) {
+            (Some(test), Some(beta)) => Channel::TestBeta { test, beta },
+            (None, Some(beta)) => Channel::Beta(beta),
+            (Some(test), None) => Channel::Test(test),
+            (None, None) => Channel::Latest,
+        };
+
+        Some(CapCutVersion {
+            major: numbers[0],
+            minor: numbers[1],
+            patch: numbers[2],
+            build: numbers.get(3).copied(),
+            channel,
+        })
+    }
+
+    /// Same major.minor.patch, ignoring build and channel
+    pub fn same_release(&self, other: &Self) -> bool {
+        (self.major, self.minor, self.patch) == (other.major, other.minor, other.patch)
+    }
+
+    /// "5.4.0"
+    pub fn base(&self) -> String {
+        format!("{}.{}.{}", self.major, self.minor, self.patch)
+    }
+
+    /// Catalog-style label: "5.4.0 (Beta6)"
+    pub fn label(&self) -> String {
+        format!("{} ({})", self.base(), self.channel)
+    }
+}
+
+impl fmt::Display for CapCutVersion {
+    /// "5.3.0.1964", with the channel appended for pre-releases
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.base())?;
+        if let Some(build) = self.build {
+            write!(f, ".{}", build)?;
+        }
+        if self.channel.is_prerelease() {
+            write!(f, " ({})", self.channel)?;
+        }
+        Ok(())
+    }
+}
+
+/// Identity encoded in an installer filename,
+/// e.g. "CapCut_5_4_0_1991_beta6_capcutpc_beta_creatortool.exe"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstallerName {
+    pub version: CapCutVersion,
+    /// Distribution flavor after the version ("capcutpc_beta_creatortool")
+    pub flavor: String,
+}
+
+impl InstallerName {
+    /// Parse a filename, path or download URL. Requires a build number.
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.split(['?', '#']).next().unwrap_or(name);
+        let file = name.rsplit(['/', '\\']).next().unwrap_or(name);
+        let stem = file
+            .len()
+            .checked_sub(4)
+            .filter(|&i| {
+                file.get(i..)
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case(".exe"))
+            })
+            .map_or(file, |i| &file[..i]);
+        // Browsers save repeated downloads as "name (1).exe"
+        let stem = stem
+            .rsplit_once(" (")
+            .filter(|(_, n)| {
+                n.strip_suffix(')')
+                    .is_some_and(|n| n.parse::<u32>().is_ok())
+            })
+            .map_or(stem, |(base, _)| base);
+
+        let version = CapCutVersion::parse(stem)?;
+        version.build?;
+
+        // Flavor: whatever follows major_minor_patch_build and an optional channel part
+        let parts: Vec<&str> = stem.split('_').collect();
+        let start = parts.iter().position(|p| p.parse::<u32>().is_ok())?;
+        let mut rest = &parts[(start + 4).min(parts.len())..];
+        if let Some(first) = rest.first() {
+            if channel_token(&first.to_ascii_lowercase(), None).is_some() {
+                rest = &rest[1..];
+            }
+        }
+
+        Some(InstallerName {
+            version,
+            flavor: rest.join("_").to_ascii_lowercase(),
+        })
+    }
+}
+
+/// Order version names semantically; unparsable names sort first, by name
+pub fn compare_names(a: &str, b: &str) -> Ordering {
+    CapCutVersion::parse(a)
+        .cmp(&CapCutVersion::parse(b))
+        .then_with(|| a.cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(s: &str) -> CapCutVersion {
+        CapCutVersion::parse(s).unwrap_or_else(|| panic!("{} should parse", s))
+    }
+
+    #[test]
+    fn numbers_compare_numerically() {
+        assert_eq!(compare_names("10.0.0.1", "9.0.0.1"), Ordering::Greater);
+        assert!(v("5.10.0") > v("5.9.9"));
+        assert!(v("5.3.0.1964") < v("5.3.0.10000"));
+        // Unparsable names sort first
+        assert_eq!(compare_names("Apps", "1.0.0"), Ordering::Less);
+    }
+
+    #[test]
+    fn channels_order_test_betas_betas_tests_then_latest() {
+        let ordered = [
+            "5.4.0 (Test1 Beta2)",
+            "5.4.0 (Beta2)",
+            "5.4.0 (Beta6)",
+            "5.4.0 (Test1)",
+            "5.4.0 (Test2)",
+            "5.4.0",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(v(pair[0]) < v(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        assert!(v("5.4.0") < v("5.4.1 (Beta1)"));
+    }
+
+    #[test]
+    fn channel_is_compared_before_build() {
+        // Label-parsed versions carry no build but still order by channel
+        assert!(v("5.4.0 (Beta6)") > v("CapCut_5_4_0_1988_beta5_capcutpc_beta_creatortool.exe"));
+        assert!(v("5.4.0 (Beta5)") < v("CapCut_5_4_0_1988_beta5_capcutpc_beta_creatortool.exe"));
+        assert!(v("5.4.0 (Beta6)") < v("CapCut_5_4_0_1991_beta6_capcutpc_beta_creatortool.exe"));
+        assert!(v("5.4.0.1991 (Beta6)") < v("5.4.0.1800"));
+        assert_ne!(v("5.4.0 (Beta6)"), v("5.4.0.1991 (Beta6)"));
+    }
+
+    #[test]
+    fn parses_folder_names_labels_and_installer_names() {
+        assert_eq!(
+            v("5.3.0.1964"),
+            CapCutVersion {
+                build: Some(1964),
+                ..CapCutVersion::new(5, 3, 0)
+            }
+        );
+        assert_eq!(v("v2.5.4").base(), "2.5.4");
+
+        let label = v("5.3.0 (Test1 Beta2)");
+        assert_eq!(label.build, None);
+        assert_eq!(label.channel, Channel::TestBeta { test: 1, beta: 2 });
+        assert_eq!(label.label(), "5.3.0 (Test1 Beta2)");
+        assert_eq!(v("5.4.0 (Beta6)").channel, Channel::Beta(6));
+        assert_eq!(v("5.4.0 (Test 2)").channel, Channel::Test(2));
+
+        let installer = v("CapCut_5_4_0_1991_beta6_capcutpc_beta_creatortool.exe");
+        assert_eq!(installer.base(), "5.4.0");
+        assert_eq!(installer.build, Some(1991));
+        assert_eq!(installer.channel, Channel::Beta(6));
+        assert_eq!(installer.to_string(), "5.4.0.1991 (Beta6)");
+
+        assert_eq!(CapCutVersion::parse("CapCut"), None);
+        assert_eq!(CapCutVersion::parse("5.4"), None);
+    }
+
+    #[test]
+    fn installer_names_carry_build_channel_and_flavor() {
+        let beta =
+            InstallerName::parse("CapCut_5_4_0_1991_beta6_capcutpc_beta_creatortool.exe").unwrap();
+        assert_eq!(beta.version.base(), "5.4.0");
+        assert_eq!(beta.version.build, Some(1991));
+        assert_eq!(beta.version.channel, Channel::Beta(6));
+        assert_eq!(beta.flavor, "capcutpc_beta_creatortool");
+
+        let stable = InstallerName::parse(
+            "https://example.com/packages/CapCut_3_9_0_1459_capcutpc_0_creatortool.exe?sign=abc",
+        )
+        .unwrap();
+        assert_eq!(stable.version.base(), "3.9.0");
+        assert_eq!(stable.version.build, Some(1459));
+        assert_eq!(stable.version.channel, Channel::Latest);
+        assert_eq!(stable.flavor, "capcutpc_0_creatortool");
+
+        // A repeated browser download names the same installer
+        let copy =
+            InstallerName::parse("C:\\Downloads\\CapCut_3_9_0_1459_capcutpc_0_creatortool (2).EXE")
+                .unwrap();
+        assert_eq!(copy, stable);
+
+        for name in [
+            "CapCut.exe",
+            "setup.exe",
+            "5.4.0 (Beta6)",
+            "CapCut_5_4_0.exe",
+        ] {
+            assert_eq!(InstallerName::parse(name), None, "{}", name);
+        }
+    }
+}
@@ -0,0 +1,528 @@
+//! Background watcher that detects (and optionally repairs) protection tampering
+//! Watches the CapCut root (and folders of protected files outside it, such as the hosts file)
+//! and reacts when a protected file changes
+
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use super::manifest::ProtectionManifest;
+use super::strategy::{self, StrategyContext, StrategySpec};
+use super::{paths, protector};
+
+/// Event emitted to the frontend when a protected file changes
+pub const TAMPER_EVENT: &str = "protection-tampered";
+
+/// Changes arriving within this window are handled together
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Upper bound on how long a batch keeps collecting events
+const MAX_BATCH: Duration = Duration::from_secs(3);
+
+/// Ignore events caused by Guard's own writes for this long after they finish
+const WRITE_GRACE: Duration = Duration::from_secs(2);
+
+/// How often to look for CapCut when it was not installed at startup
+const INSTALL_POLL: Duration = Duration::from_secs(30);
+
+/// Held shared by Guard operations that rewrite protected files and exclusively by
+/// auto-repair, so the watcher neither reports nor races Guard's own changes
+static OPERATION_LOCK: RwLock<()> = RwLock::new(());
+
+/// Changes before this instant were made by Guard itself
+static QUIET_UNTIL: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Keeps the watcher from handling changes until dropped
+pub struct OperationGuard {
+    _shared: RwLockReadGuard<'static, ()>,
+}
+
+impl Drop for OperationGuard {
+    fn drop(&mut self) {
+        quiet_for(WRITE_GRACE);
+    }
+}
+
+/// Pause tamper handling while a Guard operation rewrites protected files.
+/// Waits for an auto-repair in progress. Take it once, in the command itself.
+pub fn pause() -> OperationGuard {
+    OperationGuard {
+        _shared: OPERATION_LOCK.read().unwrap_or_else(|e| e.into_inner()),
+    }
+}
+
+fn quiet_for(duration: Duration) {
+    if let Ok(mut until) = QUIET_UNTIL.lock() {
+        *until = Some(Instant::now() + duration);
+    }
+}
+
+/// Whether changes arriving now are most likely Guard's own writes
+fn is_quiet() -> bool {
+    QUIET_UNTIL
+        .lock()
+        .ok()
+        .and_then(|until| *until)
+        .is_some_and(|until| Instant::now() < until)
+}
+
+/// Persisted watcher settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatcherSettings {
+    /// Re-apply protection automatically when tampering is detected
+    pub auto_repair: bool,
+}
+
+/// Details of a detected tampering
+#[derive(Debug, Clone, Serialize)]
+pub struct TamperEvent {
+    /// Protected files that changed
+    pub paths: Vec<String>,
+    /// Strategies that are no longer in place
+    pub broken_strategies: Vec<String>,
+    /// When the change was detected (Unix timestamp)
+    pub detected_at: u64,
+    /// Whether protection was re-applied automatically
+    pub repaired: bool,
+    pub message: String,
+}
+
+fn get_settings_path() -> Option<PathBuf> {
+    paths::get_guard_data_dir().map(|p| p.join("watcher.json"))
+}
+
+fn get_log_path() -> Option<PathBuf> {
+    paths::get_guard_data_dir().map(|p| p.join("watcher.log"))
+}
+
+fn load_settings() -> WatcherSettings {
+    get_settings_path()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+/// Append a line to the watcher log
+fn append_log(line: &str) {
+    let Some(path) = get_log_path() else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).ok();
+    }
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&path) {
+        writeln!(file, "{}", line).ok();
+    }
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Paths currently under protection (files touched by the applied strategies)
+fn protected_paths(ctx: &StrategyContext) -> Vec<PathBuf> {
+    let manifest = ProtectionManifest::open_default().unwrap_or_default();
+    let mut protected: Vec<PathBuf> = manifest
+        .entries
+        .iter()
+        .map(|e| PathBuf::from(&e.path))
+        .collect();
+    for spec in &manifest.applied {
+        if let Some(strategy) = strategy::strategy_by_id(&spec.id) {
+            protected.extend(strategy.touched_paths(ctx, &spec.options));
+        }
+    }
+    protected
+}
+
+/// Folders of protected files outside the CapCut root, which its recursive watch misses
+fn outside_root_dirs(ctx: &StrategyContext, protected: &[PathBuf]) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for path in protected
+        .iter()
+        .filter(|p| !p.starts_with(&ctx.capcut_root))
+    {
+        if let Some(parent) = path.parent() {
+            if !dirs.iter().any(|d| same_path(d, parent)) {
+                dirs.push(parent.to_path_buf());
+            }
+        }
+    }
+    dirs
+}
+
+/// Watch the folders of protected files outside the CapCut root, following what is applied
+fn watch_outside_root(
+    watcher: &mut impl Watcher,
+    ctx: &StrategyContext,
+    watched: &mut Vec<PathBuf>,
+) {
+    let wanted = outside_root_dirs(ctx, &protected_paths(ctx));
+    watched.retain(|dir| {
+        let keep = wanted.contains(dir);
+        if !keep {
+            watcher.unwatch(dir).ok();
+        }
+        keep
+    });
+    for dir in wanted {
+        if watched.contains(&dir) {
+            continue;
+        }
+        // Watch the folder, not the file, so a file replaced by a rename is still seen
+        match watcher.watch(&dir, RecursiveMode::NonRecursive) {
+            Ok(()) => watched.push(dir),
+            Err(e) => append_log(&format!(
+                "[{}] Could not watch {}: {}",
+                now(),
+                dir.display(),
+                e
+            )),
+        }
+    }
+}
+
+/// Check which applied strategies owning the changed paths are no longer active
+fn broken_strategies(ctx: &StrategyContext, changed: &[PathBuf]) -> Vec<StrategySpec> {
+    let manifest = ProtectionManifest::open_default().unwrap_or_default();
+    manifest
+        .applied
+        .into_iter()
+        .filter(|spec| {
+            let Some(strategy) = strategy::strategy_by_id(&spec.id) else {
+                return false;
+            };
+            let owned = strategy.touched_paths(ctx, &spec.options);
+            changed
+                .iter()
+                .any(|p| owned.iter().any(|o| same_path(o, p)))
+                && !strategy.verify(ctx, &spec.options).active
+        })
+        .collect()
+}
+
+/// Handle a batch of changed protected paths
+fn handle_changes(app: &AppHandle, ctx: &StrategyContext, changed: Vec<PathBuf>) -> bool {
+    let broken = broken_strategies(ctx, &changed);
+    if broken.is_empty() {
+        return false;
+    }
+
+    let names: Vec<String> = broken.iter().map(|s| s.id.clone()).collect();
+    let changed_list: Vec<String> = changed
+        .iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+
+    let settings = load_settings();
+    let mut repaired = false;
+    let mut message = format!(
+        "Protection changed outside Version Guard: {}",
+        names.join(", ")
+    );
+
+    if settings.auto_repair {
+        let result = protector::apply_strategies(&broken);
+        repaired = result.success;
+        if repaired {
+            message.push_str(" - protection re-applied");
+        } else {
+            message.push_str(&format!(
+                " - repair failed: {}",
+                result.error.unwrap_or_default()
+            ));
+        }
+    }
+
+    let event = TamperEvent {
+        paths: changed_list,
+        broken_strategies: names,
+        detected_at: now(),
+        repaired,
+        message,
+    };
+    append_log(&format!(
+        "[{}] {} ({})",
+        event.detected_at,
+        event.message,
+        event.paths.join(", ")
+    ));
+    let _ = app.emit(TAMPER_EVENT, event);
+    repaired
+}
+
+/// Whether a notify event changes file content or existence
+fn is_relevant(event: &Event) -> bool {
+    matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    )
+}
+
+/// Add protected paths changed by a batch of events to `changed`
+fn collect_changes(
+    protected: &[PathBuf],
+    events: Vec<notify::Result<Event>>,
+    changed: &mut Vec<PathBuf>,
+) {
+    for event in events.into_iter().filter_map(|e| e.ok()) {
+        if !is_relevant(&event) {
+            continue;
+        }
+        for path in event.paths {
+            if protected.iter().any(|p| same_path(p, &path)) && !changed.contains(&path) {
+                changed.push(path);
+            }
+        }
+    }
+}
+
+/// Exclusive hold on Guard operations once none is running and their writes have settled.
+/// Holding it keeps Guard operations from starting mid-repair.
+/// Changes seen before then are kept and re-verified, since a Guard operation leaves protection
+/// either verified or no longer applied.
+fn exclusive_when_idle() -> Option<RwLockWriteGuard<'static, ()>> {
+    let lock = match OPERATION_LOCK.try_write() {
+        Ok(lock) => lock,
+        Err(TryLockError::Poisoned(e)) => e.into_inner(),
+        Err(TryLockError::WouldBlock) => return None,
+    };
+    (!is_quiet()).then_some(lock)
+}
+
+/// Resolve the CapCut root, polling until CapCut is installed
+fn wait_for_capcut() -> StrategyContext {
+    let mut logged = false;
+    loop {
+        if let Some(capcut_paths) = paths::resolve_capcut_paths() {
+            return StrategyContext {
+                capcut_root: capcut_paths.root,
+                apps_path: capcut_paths.apps,
+            };
+        }
+        if !logged {
+            append_log(&format!(
+                "[{}] CapCut not found - waiting for it to be installed",
+                now()
+            ));
+            logged = true;
+        }
+        std::thread::sleep(INSTALL_POLL);
+    }
+}
+
+/// Start watching the CapCut root in a background thread
+pub fn start(app: AppHandle) {
+    std::thread::spawn(move || {
+        let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+        let mut watcher = match notify::recommended_watcher(tx) {
+            Ok(w) => w,
+            Err(e) => {
+                append_log(&format!("[{}] Watcher unavailable: {}", now(), e));
+                return;
+            }
+        };
+
+        let mut ctx = wait_for_capcut();
+        let mut logged = false;
+        while let Err(e) = watcher.watch(&ctx.capcut_root, RecursiveMode::Recursive) {
+            if !logged {
+                append_log(&format!(
+                    "[{}] Could not watch {}: {} - retrying",
+                    now(),
+                    ctx.capcut_root.display(),
+                    e
+                ));
+                logged = true;
+            }
+            std::thread::sleep(INSTALL_POLL);
+            ctx = wait_for_capcut();
+        }
+
+        let mut outside = Vec::new();
+        // Protected paths changed while a Guard operation ran; checked once it has finished
+        let mut pending: Vec<PathBuf> = Vec::new();
+        loop {
+            // Protection applied since the last batch may own files outside the root
+            watch_outside_root(&mut watcher, &ctx, &mut outside);
+            let timeout = if pending.is_empty() {
+                INSTALL_POLL
+            } else {
+                DEBOUNCE
+            };
+            let mut events = Vec::new();
+            match rx.recv_timeout(timeout) {
+                Ok(first) => {
+                    // Collect everything that arrives within the debounce window
+                    // (bounded, since cache writes elsewhere in the root never stop)
+                    let batch_started = Instant::now();
+                    events.push(first);
+                    while batch_started.elapsed() < MAX_BATCH {
+                        match rx.recv_timeout(DEBOUNCE) {
+                            Ok(next) => events.push(next),
+                            Err(_) => break,
+                        }
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) if pending.is_empty() => continue,
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            collect_changes(&protected_paths(&ctx), events, &mut pending);
+            if pending.is_empty() {
+                continue;
+            }
+            let Some(_exclusive) = exclusive_when_idle() else {
+                continue;
+            };
+            let changed = std::mem::take(&mut pending);
+            if handle_changes(&app, &ctx, changed) {
+                quiet_for(WRITE_GRACE);
+            }
+        }
+    });
+}
+
+/// Compare paths case-insensitively (Windows file system semantics)
+fn same_path(a: &Path, b: &Path) -> bool {
+    a.to_string_lossy()
+        .eq_ignore_ascii_case(&b.to_string_lossy())
+}
+
+/// Get watcher settings
+#[tauri::command]
+pub fn get_watcher_settings() -> WatcherSettings {
+    load_settings()
+}
+
+/// Update watcher settings
+#[tauri::command]
+pub fn set_watcher_settings(settings: WatcherSettings) -> Result<(), String> {
+    let path = get_settings_path().ok_or("Could not determine settings directory")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    fs::write(path, content).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::fsops::FsOps;
+    use crate::commands::test_support::{scratch_install, scratch_local_app_data};
+    use notify::event::{DataChange, ModifyKind};
+
+    /// The operation lock and quiet period are process-wide
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    fn serial() -> std::sync::MutexGuard<'static, ()> {
+        SERIAL.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn end_quiet_period() {
+        *QUIET_UNTIL.lock().unwrap() = None;
+    }
+
+    #[test]
+    fn protected_files_outside_the_root_get_their_folder_watched() {
+        let ctx = StrategyContext {
+            capcut_root: PathBuf::from("/data/CapCut"),
+            apps_path: PathBuf::from("/data/CapCut/Apps"),
+        };
+        let protected = [
+            ctx.apps_path.join("ProductInfo.xml"),
+            ctx.capcut_root
+                .join("User Data")
+                .join("Download")
+                .join("update.exe"),
+            PathBuf::from("/windows/drivers/etc/hosts"),
+            PathBuf::from("/windows/drivers/etc/hosts.ics"),
+        ];
+        assert_eq!(
+            outside_root_dirs(&ctx, &protected),
+            [PathBuf::from("/windows/drivers/etc")]
+        );
+    }
+
+    #[test]
+    fn guard_operations_silence_the_watcher() {
+        let _serial = serial();
+        let paused = pause();
+        assert!(matches!(
+            OPERATION_LOCK.try_write(),
+            Err(TryLockError::WouldBlock)
+        ));
+        drop(paused);
+
+        assert!(OPERATION_LOCK.try_write().is_ok());
+        // Events still in flight from the operation are attributed to it
+        assert!(is_quiet());
+    }
+
+    #[test]
+    fn tampering_during_an_operation_is_checked_once_it_finishes() {
+        let _serial = serial();
+        let (_guard, dir) = scratch_local_app_data("watcher-tamper-during-operation");
+        let apps = scratch_install(&dir, &["5.0.0.100"], "5.0.0.100");
+        let blockers = [StrategySpec::new("blocker_files")];
+        assert!(protector::apply_strategies(&blockers).success);
+        let ctx = protector::strategy_context().unwrap();
+        let product_info = apps.join("ProductInfo.xml");
+
+        // Another program rewrites a blocker while a Guard operation holds the lock
+        let paused = pause();
+        FsOps::execute().set_readonly(&product_info, false).unwrap();
+        fs::write(&product_info, "<ProductInfo />").unwrap();
+        let event = Event::new(EventKind::Modify(ModifyKind::Data(DataChange::Content)))
+            .add_path(product_info.clone());
+        let mut pending = Vec::new();
+        collect_changes(&protected_paths(&ctx), vec![Ok(event)], &mut pending);
+        assert_eq!(pending, std::slice::from_ref(&product_info));
+        assert!(exclusive_when_idle().is_none());
+
+        // Still held back during the quiet period after the operation
+        drop(paused);
+        assert!(exclusive_when_idle().is_none());
+
+        end_quiet_period();
+        let _exclusive = exclusive_when_idle().unwrap();
+        let broken: Vec<String> = broken_strategies(&ctx, &pending)
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(broken, ["blocker_files"]);
+    }
+
+    #[test]
+    fn changes_by_guard_itself_verify_clean_afterwards() {
+        let _serial = serial();
+        let (_guard, dir) = scratch_local_app_data("watcher-own-changes");
+        let apps = scratch_install(&dir, &["5.0.0.100"], "5.0.0.100");
+        let ctx = protector::strategy_context().unwrap();
+        let product_info = apps.join("ProductInfo.xml");
+
+        let paused = pause();
+        assert!(protector::apply_strategies(&[StrategySpec::new("blocker_files")]).success);
+        let event = Event::new(EventKind::Create(notify::event::CreateKind::File))
+            .add_path(product_info.clone());
+        let mut pending = Vec::new();
+        collect_changes(&protected_paths(&ctx), vec![Ok(event)], &mut pending);
+        drop(paused);
+        end_quiet_period();
+
+        assert!(exclusive_when_idle().is_some());
+        assert_eq!(pending, [product_info]);
+        assert!(broken_strategies(&ctx, &pending).is_empty());
+    }
+}
@@ -3,7 +3,10 @@
 
 mod commands;
 
-use commands::{autostart, backup, cleaner, paths, process, protector, scanner, switcher};
+use commands::{
+    autostart, backup, catalog_query, catalog_store, cleaner, downloads, features, installers,
+    mirrors, parking, paths, process, protector, recommend, scanner, switcher, watcher,
+};
 use tauri::{
     menu::{Menu, MenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
@@ -81,6 +84,8 @@ pub fn run() {
         .setup(|app| {
             // Initialize system tray
             setup_tray(app)?;
+            // Watch protected files for tampering
+            watcher::start(app.handle().clone());
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -89,6 +94,29 @@ pub fn run() {
             scanner::get_all_archive_versions,
             scanner::scan_versions,
             scanner::get_capcut_paths,
+            scanner::get_launcher_info,
+            // Catalog commands
+            catalog_query::query_catalog,
+            features::compare_versions,
+            features::get_feature_matrix,
+            recommend::recommend_versions,
+            installers::identify_installer,
+            catalog_store::update_catalog,
+            catalog_store::get_catalog_info,
+            catalog_store::get_catalog_settings,
+            catalog_store::set_catalog_settings,
+            // Download commands
+            downloads::start_download,
+            downloads::cancel_download,
+            downloads::list_downloads,
+            mirrors::get_mirror_health,
+            mirrors::clear_mirror_health,
+            // Installer library commands
+            installers::list_installer_library,
+            installers::import_installer,
+            installers::find_local_installer,
+            installers::prune_installers,
+            installers::plan_prune_installers,
             // Path resolution commands
             paths::get_path_info,
             paths::validate_custom_capcut_path,
@@ -108,14 +136,27 @@ pub fn run() {
             // Cleaner commands
             cleaner::calculate_cache_size,
             cleaner::clean_cache,
+            cleaner::plan_clean_cache,
             // Protector commands
             protector::delete_versions,
             protector::apply_protection,
             protector::run_full_protection,
             protector::check_protection_status,
             protector::remove_protection,
+            protector::plan_delete_versions,
+            protector::plan_full_protection,
+            protector::plan_remove_protection,
             // Switcher commands
             switcher::switch_version,
+            switcher::plan_switch_version,
+            // Parking commands
+            parking::park_version,
+            parking::unpark_version,
+            parking::plan_park_version,
+            parking::plan_unpark_version,
+            // Watcher commands
+            watcher::get_watcher_settings,
+            watcher::set_watcher_settings,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");